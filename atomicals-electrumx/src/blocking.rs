@@ -0,0 +1,165 @@
+//! Blocking wrapper of the [`Api`](crate::Api), for use outside of a tokio runtime.
+//!
+//! Every call runs on a runtime owned by the client. Don't call it from within another async
+//! runtime, it panics there.
+
+// std
+use std::future::Future;
+// crates.io
+use bitcoin::{block::Header, Network, Txid};
+use serde_json::Value;
+use tokio::runtime::{Builder, Runtime};
+// atomicals-electrumx
+use crate::{prelude::*, r#type::*, Api};
+
+// Forward each method to the async API on the client's runtime.
+macro_rules! blocking {
+	($(
+		$(#[$attr:meta])*
+		fn $name:ident(&self $(, $arg:ident: $ty:ty)*) -> $ret:ty;
+	)*) => {
+		$(
+			$(#[$attr])*
+			pub fn $name(&self $(, $arg: $ty)*) -> Result<$ret> {
+				self.block_on(self.inner.$name($($arg),*))
+			}
+		)*
+	};
+}
+
+/// Blocking atomicals electrumx client.
+#[derive(Debug)]
+pub struct Client<A = crate::Client> {
+	/// Wrapped async client.
+	pub inner: A,
+	runtime: Runtime,
+}
+impl Client {
+	/// Build a client with default settings, picking the transport by the URI's scheme.
+	pub fn new<S>(network: Network, uri: S) -> Result<Self>
+	where
+		S: Into<String>,
+	{
+		Self::from_api(crate::Client::new(network, uri)?)
+	}
+}
+impl<A> Client<A>
+where
+	A: Api,
+{
+	blocking! {
+		/// Make a request at `blockchain.atomicals.get_by_ticker`.
+		fn get_by_ticker(&self, ticker: &str) -> Ticker;
+		/// Make a request at `blockchain.atomicals.get_ft_info`.
+		fn get_ft_info(&self, atomical_id: &str) -> ResponseResult<Ft>;
+		/// Make a request at `blockchain.atomicals.get`.
+		fn get_atomical(&self, atomical_id: &str) -> ResponseResult<Atomical>;
+		/// Make a request at `blockchain.atomicals.get_by_realm`.
+		fn get_by_realm(&self, realm: &str) -> Realm;
+		/// Make a request at `blockchain.atomicals.get_by_container`.
+		fn get_by_container(&self, container: &str) -> Container;
+		/// Make a request at `blockchain.atomicals.get_container_item_validation`.
+		#[allow(clippy::too_many_arguments)]
+		fn get_container_item_validation(
+			&self,
+			container: &str,
+			item: &str,
+			bitworkc: &str,
+			bitworkr: &str,
+			main: &str,
+			main_hash: &str,
+			proof: &Value
+		) -> ItemValidation;
+		/// Make a request at `blockchain.atomicals.get_state`.
+		fn get_state(&self, atomical_id: &str) -> ResponseResult<AtomicalState>;
+		/// Make a request at `blockchain.atomicals.get_global`.
+		fn get_global(&self) -> Global;
+		/// Make a request at `blockchain.atomicals.get_realm_info`.
+		fn get_realm_info(&self, full_name: &str) -> ResponseResult<RealmInfo>;
+		/// Make a request at `blockchain.atomicals.get_location`.
+		fn get_location(&self, atomical_id: &str) -> ResponseResult<AtomicalLocation>;
+		/// Make a request at `blockchain.atomicals.get_container_items`.
+		fn get_container_items(
+			&self,
+			container: &str,
+			limit: u32,
+			offset: u32
+		) -> ResponseResult<ContainerItems>;
+		/// Make a request at `blockchain.atomicals.listscripthash`.
+		fn list_scripthash(&self, scripthash: &str) -> ScripthashAtomicals;
+		/// Make a request at `blockchain.atomicals.get_holders`.
+		fn get_holders(
+			&self,
+			atomical_id: &str,
+			limit: u32,
+			offset: u32
+		) -> ResponseResult<Holders>;
+		/// Make a request at `blockchain.atomicals.find_tickers`.
+		fn find_tickers(
+			&self,
+			prefix: &str,
+			verified_only: bool,
+			limit: u32,
+			offset: u32
+		) -> ResponseResult<Vec<TickerEntry>>;
+		/// Make a request at `blockchain.scripthash.listunspent` for the address.
+		fn get_unspent_address(&self, address: &str) -> Vec<Utxo>;
+		/// Make a request at `blockchain.scripthash.listunspent` for each address in one batch.
+		fn get_unspent_addresses(&self, addresses: Vec<&str>) -> Vec<Vec<Utxo>>;
+		/// Make a request at `blockchain.scripthash.listunspent`.
+		fn get_unspent_scripthash(&self, scripthash: &str) -> Vec<Utxo>;
+		/// Make a request at `blockchain.scripthash.listunspent` for each scripthash in one batch.
+		fn get_unspent_scripthashes(&self, scripthashes: Vec<&str>) -> Vec<Vec<Utxo>>;
+		/// Classify the UTXOs, looking up the type of each atomical they carry once.
+		fn classify_utxos(&self, utxos: Vec<Utxo>) -> Vec<(Utxo, UtxoKind)>;
+		/// Wait until the address holds a UTXO of at least the given value.
+		fn wait_until_utxo(&self, address: &str, satoshis: u64) -> Utxo;
+		/// Make a request at `blockchain.transaction.get`.
+		fn get_transaction(&self, txid: &str) -> String;
+		/// Make a request at `blockchain.estimatefee`.
+		fn estimate_fee(&self, target: u16) -> f64;
+		/// Make a request at `blockchain.block.header`.
+		fn get_block_header(&self, height: u64) -> Header;
+		/// Make a request at `blockchain.transaction.broadcast`.
+		fn broadcast(&self, tx: &str) -> Txid;
+	}
+
+	/// Wrap the async client.
+	pub fn from_api(inner: A) -> Result<Self> {
+		// The electrum transport reads its connection on a background task, which needs a worker.
+		let runtime = Builder::new_multi_thread().worker_threads(1).enable_all().build()?;
+
+		Ok(Self { inner, runtime })
+	}
+
+	fn block_on<F>(&self, future: F) -> F::Output
+	where
+		F: Future,
+	{
+		self.runtime.block_on(future)
+	}
+}
+#[test]
+fn blocking_should_work() {
+	// atomicals-electrumx
+	use crate::{
+		mock::{Ft, Mock},
+		ElectrumXBuilder,
+	};
+
+	// The mock needs a runtime of its own, the client must not run on it.
+	let rt = Runtime::new().unwrap();
+	let m = rt.block_on(Mock::start());
+	let id = m.state().add_ft(Ft::easy("atomicalsir"));
+	let c = Client::from_api(
+		ElectrumXBuilder::default().network(Network::Testnet).base_uri(&m.uri).build().unwrap(),
+	)
+	.unwrap();
+
+	assert_eq!(c.get_by_ticker("atomicalsir").unwrap().atomical_id, Some(id.to_string()));
+	assert_eq!(c.get_ft_info(&id.to_string()).unwrap().result.mint_amount, 1_000);
+	assert_eq!(c.get_global().unwrap().height, 1);
+	assert!(c.get_unspent_addresses(vec!["tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx"]).unwrap()
+		[0]
+	.is_empty());
+}
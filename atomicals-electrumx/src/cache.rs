@@ -0,0 +1,357 @@
+//! Response cache in front of any [`Api`].
+//!
+//! Static data is kept for good and data which only changes with the chain is kept until the
+//! next block. Methods without a TTL, such as the UTXO lookups, always go through. Lookups by
+//! name are only cached once the name is verified.
+
+// std
+use std::{
+	collections::HashMap,
+	sync::{
+		atomic::{AtomicU64, Ordering},
+		Mutex as StdMutex,
+	},
+	time::Duration,
+};
+// crates.io
+use bitcoin::Network;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use tokio::time::Instant;
+// atomicals-electrumx
+use crate::{electrum::Subscription, prelude::*, Api, Config, Http};
+
+/// How long a cached response stays fresh.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Ttl {
+	/// For good.
+	Permanent,
+	/// Until a new block shows up.
+	Block,
+}
+
+/// Caching wrapper of an [`Api`].
+#[derive(Debug)]
+pub struct Cache<A> {
+	/// Wrapped client.
+	pub inner: A,
+	/// TTLs by method, the responses of the other methods are not cached.
+	pub ttls: HashMap<String, Ttl>,
+	/// Check the chain height at most this often before serving a per block response.
+	pub height_interval: Duration,
+	height: AtomicU64,
+	last_height_check: StdMutex<Option<Instant>>,
+	entries: StdMutex<HashMap<(String, String), (Ttl, Value)>>,
+}
+impl<A> Cache<A>
+where
+	A: Api,
+{
+	/// Wrap the client with the default TTLs.
+	pub fn new(inner: A) -> Self {
+		let ttls = [
+			// A verified ticker is never taken by another atomical.
+			("blockchain.atomicals.get_by_ticker", Ttl::Permanent),
+			("blockchain.transaction.get", Ttl::Permanent),
+			("blockchain.atomicals.get_ft_info", Ttl::Block),
+			("blockchain.atomicals.get", Ttl::Block),
+			("blockchain.atomicals.get_by_realm", Ttl::Block),
+			("blockchain.atomicals.get_by_container", Ttl::Block),
+			("blockchain.atomicals.get_realm_info", Ttl::Block),
+			("blockchain.estimatefee", Ttl::Block),
+		]
+		.into_iter()
+		.map(|(m, t)| (m.to_owned(), t))
+		.collect();
+
+		Self {
+			inner,
+			ttls,
+			height_interval: Duration::from_secs(10),
+			height: AtomicU64::new(0),
+			last_height_check: StdMutex::new(None),
+			entries: StdMutex::new(HashMap::new()),
+		}
+	}
+
+	/// Drop every cached response.
+	pub fn invalidate(&self) {
+		self.entries.lock().unwrap().clear();
+	}
+
+	// Drop the per block responses once the chain moves on.
+	fn observe_height(&self, height: u64) {
+		let last = self.height.fetch_max(height, Ordering::SeqCst);
+
+		if height > last {
+			tracing::debug!("new block at height {height}, dropping the per block responses");
+
+			self.entries.lock().unwrap().retain(|_, (t, _)| *t == Ttl::Permanent);
+		}
+	}
+
+	async fn check_height(&self) {
+		{
+			let mut last = self.last_height_check.lock().unwrap();
+
+			if last.is_some_and(|t| t.elapsed() < self.height_interval) {
+				return;
+			}
+
+			*last = Some(Instant::now());
+		}
+
+		match self.inner.get_global().await {
+			Ok(g) => self.observe_height(g.height),
+			// Keep serving the cache, the next check might succeed.
+			Err(e) => tracing::warn!("failed to check the chain height due to {e}"),
+		}
+	}
+
+	fn store(&self, ttl: Ttl, key: (String, String), response: &Value) {
+		if let Some(h) = response["response"]["global"]["height"].as_u64() {
+			self.observe_height(h);
+		}
+
+		// A name might still be claimed until it is verified.
+		if response["response"]["result"]["status"].as_str().is_none_or(|s| s == "verified") {
+			self.entries.lock().unwrap().insert(key, (ttl, response.to_owned()));
+		}
+	}
+}
+impl<A> Config for Cache<A>
+where
+	A: Config,
+{
+	fn network(&self) -> &Network {
+		self.inner.network()
+	}
+
+	fn base_uri(&self) -> &str {
+		self.inner.base_uri()
+	}
+
+	fn wait_timeout(&self) -> Option<Duration> {
+		self.inner.wait_timeout()
+	}
+}
+impl<A> Http for Cache<A>
+where
+	A: Api,
+{
+	async fn post<U, P, R>(&self, uri: U, params: P) -> Result<R>
+	where
+		U: Send + Sync + AsRef<str>,
+		P: Send + Sync + Serialize,
+		R: DeserializeOwned,
+	{
+		let u = uri.as_ref();
+		let method = u.rsplit_once('/').map(|(_, m)| m).unwrap_or(u);
+		let Some(ttl) = self.ttls.get(method).copied() else {
+			return self.inner.post(u, params).await;
+		};
+		let key = (method.to_owned(), serde_json::to_string(&params)?);
+
+		if ttl == Ttl::Block {
+			self.check_height().await;
+		}
+
+		let cached = self.entries.lock().unwrap().get(&key).map(|(_, v)| v.to_owned());
+		let r = match cached {
+			Some(r) => r,
+			None => {
+				let r = self.inner.post::<_, _, Value>(u, &params).await?;
+
+				self.store(ttl, key, &r);
+
+				r
+			},
+		};
+
+		R::deserialize(&r).map_err(|source| Error::Decode { source, body: r.to_string() })
+	}
+
+	// Serve what is cached and send the rest to the wrapped client in one batch.
+	async fn post_batch<U, P, R>(&self, requests: Vec<(U, P)>) -> Result<Vec<Result<R>>>
+	where
+		U: Send + Sync + AsRef<str>,
+		P: Send + Sync + Serialize,
+		R: Send + DeserializeOwned,
+	{
+		let mut results = Vec::with_capacity(requests.len());
+		let mut misses = Vec::new();
+		let mut height_checked = false;
+
+		for (i, (u, p)) in requests.iter().enumerate() {
+			let u = u.as_ref();
+			let method = u.rsplit_once('/').map(|(_, m)| m).unwrap_or(u);
+			let key = match self.ttls.get(method).copied() {
+				Some(ttl) => {
+					if ttl == Ttl::Block && !height_checked {
+						self.check_height().await;
+
+						height_checked = true;
+					}
+
+					Some((ttl, (method.to_owned(), serde_json::to_string(p)?)))
+				},
+				None => None,
+			};
+			let cached = key
+				.as_ref()
+				.and_then(|(_, k)| self.entries.lock().unwrap().get(k).map(|(_, v)| v.to_owned()));
+
+			if cached.is_none() {
+				misses.push((i, key, u, p));
+			}
+
+			results.push(cached.map(Ok));
+		}
+
+		if !misses.is_empty() {
+			let rs = self
+				.inner
+				.post_batch::<_, _, Value>(misses.iter().map(|(_, _, u, p)| (*u, *p)).collect())
+				.await?;
+
+			for ((i, key, _, _), r) in misses.into_iter().zip(rs) {
+				if let (Ok(r), Some((ttl, key))) = (&r, key) {
+					self.store(ttl, key, r);
+				}
+
+				results[i] = Some(r);
+			}
+		}
+
+		Ok(results
+			.into_iter()
+			.map(|r| {
+				let r = r.expect("one result per request")?;
+
+				R::deserialize(&r).map_err(|source| Error::Decode { source, body: r.to_string() })
+			})
+			.collect())
+	}
+
+	async fn subscribe<S>(&self, scripthash: S) -> Result<Option<Subscription>>
+	where
+		S: Send + Sync + AsRef<str>,
+	{
+		self.inner.subscribe(scripthash).await
+	}
+}
+#[test]
+fn cache_should_work() {
+	// crates.io
+	use tokio::runtime::Runtime;
+	// atomicals-electrumx
+	use crate::{
+		mock::{Ft, Mock},
+		ElectrumXBuilder,
+	};
+
+	Runtime::new().unwrap().block_on(async {
+		let m = Mock::start().await;
+		let id = m.state().add_ft(Ft::easy("atomicalsir"));
+		let mut c = Cache::new(
+			ElectrumXBuilder::default().network(Network::Testnet).base_uri(&m.uri).build().unwrap(),
+		);
+
+		c.height_interval = Duration::ZERO;
+
+		let mint_count =
+			|| async { c.get_ft_info(id.to_string()).await.unwrap().result.dft_info.mint_count };
+
+		assert_eq!(c.get_by_ticker("sir").await.unwrap().atomical_id, None);
+
+		m.state().add_ft(Ft::easy("sir"));
+
+		assert!(c.get_by_ticker("sir").await.unwrap().atomical_id.is_some());
+		assert_eq!(c.get_by_ticker("atomicalsir").await.unwrap().atomical_id, Some(id.to_string()));
+		assert_eq!(mint_count().await, 0);
+
+		m.state().tickers.clear();
+		m.state().fts.get_mut(&id).unwrap().mint_count = 1;
+
+		// Served from the cache within the same block.
+		assert_eq!(c.get_by_ticker("atomicalsir").await.unwrap().atomical_id, Some(id.to_string()));
+		assert_eq!(mint_count().await, 0);
+
+		m.state().height += 1;
+
+		// The ticker resolution is kept across blocks.
+		assert_eq!(mint_count().await, 1);
+		assert_eq!(c.get_by_ticker("atomicalsir").await.unwrap().atomical_id, Some(id.to_string()));
+
+		c.invalidate();
+
+		assert_eq!(c.get_by_ticker("atomicalsir").await.unwrap().atomical_id, None);
+
+		// UTXOs are never cached.
+		let a = "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx";
+
+		assert!(c.get_unspent_address(a).await.unwrap().is_empty());
+
+		m.state().fund(&a.parse::<bitcoin::Address<_>>().unwrap().assume_checked(), 1_000);
+
+		assert_eq!(c.get_unspent_address(a).await.unwrap().len(), 1);
+	});
+}
+#[test]
+fn cache_post_batch_should_work() {
+	// std
+	use std::sync::atomic::Ordering;
+	// crates.io
+	use tokio::runtime::Runtime;
+	// atomicals-electrumx
+	use crate::{electrum::ElectrumBuilder, pool::Pool};
+
+	Runtime::new().unwrap().block_on(async {
+		let (uri, round_trips) = crate::electrum::mock_counted().await;
+		let c = Cache::new(
+			Pool::new(vec![ElectrumBuilder::default()
+				.timeout(Duration::from_secs(5))
+				.base_uri(uri)
+				.build()
+				.unwrap()])
+			.unwrap(),
+		);
+		let tx = c.uri_of("blockchain.transaction.get");
+		let utxos = c.uri_of("blockchain.scripthash.listunspent");
+		let batch = |rs: Vec<(&str, &str)>| {
+			let rs = rs.into_iter().map(|(u, p)| (u.to_owned(), [p.to_owned()])).collect();
+
+			async {
+				c.post_batch::<_, _, Value>(rs)
+					.await
+					.unwrap()
+					.into_iter()
+					.map(|r| r.unwrap()["response"].clone())
+					.collect::<Vec<_>>()
+			}
+		};
+
+		c.inner.probe(true).await;
+
+		let n = round_trips.load(Ordering::SeqCst);
+
+		assert_eq!(batch(vec![(&tx, "00"), (&tx, "01"), (&tx, "02")]).await, ["00", "01", "02"]);
+		assert_eq!(round_trips.load(Ordering::SeqCst), n + 1);
+
+		// Served from the cache, in the order of the requests.
+		assert_eq!(batch(vec![(&tx, "02"), (&tx, "00")]).await, ["02", "00"]);
+		assert_eq!(round_trips.load(Ordering::SeqCst), n + 1);
+
+		// Only the misses are sent, UTXOs are never cached.
+		assert_eq!(
+			batch(vec![(&tx, "01"), (&utxos, "ff"), (&tx, "03"), (&utxos, "ff")]).await,
+			[
+				serde_json::json!("01"),
+				serde_json::json!([]),
+				serde_json::json!("03"),
+				serde_json::json!([])
+			]
+		);
+		assert_eq!(round_trips.load(Ordering::SeqCst), n + 2);
+	});
+}
@@ -0,0 +1,195 @@
+//! Chain-facing queries, which any Bitcoin backend can answer without an Atomicals index.
+
+// std
+use std::{future::Future, time::Duration};
+// crates.io
+use bitcoin::{Amount, Txid};
+use tokio::time::{self, Instant};
+// atomicals-electrumx
+use crate::{prelude::*, r#type::Utxo, Api};
+
+/// Chain-facing queries.
+pub trait Chain: Send + Sync {
+	/// Get the UTXOs of the address, sorted by value.
+	///
+	/// Backends without an Atomicals index leave [`Utxo::atomicals`] empty.
+	fn utxos<S>(&self, address: S) -> impl Future<Output = Result<Vec<Utxo>>> + Send
+	where
+		S: Send + Sync + AsRef<str>;
+
+	/// Wait until a UTXO without atomicals and worth at least `satoshis` is found.
+	fn wait_for_utxo<S>(
+		&self,
+		address: S,
+		satoshis: u64,
+	) -> impl Future<Output = Result<Utxo>> + Send
+	where
+		S: Send + Sync + AsRef<str>;
+
+	/// Estimate the fee rate in sat/vB to confirm within `target` blocks.
+	fn fee_rate(&self, target: u16) -> impl Future<Output = Result<u64>> + Send;
+
+	/// Check if the mempool would accept the transaction, without broadcasting it.
+	///
+	/// Backends which cannot tell accept everything.
+	fn test_mempool_accept<S>(&self, tx: S) -> impl Future<Output = Result<()>> + Send
+	where
+		S: Send + Sync + AsRef<str>,
+	{
+		let _ = tx;
+
+		async { Ok(()) }
+	}
+
+	/// Broadcast the transaction.
+	fn send_transaction<S>(&self, tx: S) -> impl Future<Output = Result<Txid>> + Send
+	where
+		S: Send + Sync + AsRef<str>;
+}
+impl<T> Chain for T
+where
+	T: Api,
+{
+	async fn utxos<S>(&self, address: S) -> Result<Vec<Utxo>>
+	where
+		S: Send + Sync + AsRef<str>,
+	{
+		self.get_unspent_address(address).await
+	}
+
+	async fn wait_for_utxo<S>(&self, address: S, satoshis: u64) -> Result<Utxo>
+	where
+		S: Send + Sync + AsRef<str>,
+	{
+		self.wait_until_utxo(address, satoshis).await
+	}
+
+	async fn fee_rate(&self, target: u16) -> Result<u64> {
+		btc_per_kvb_to_sat_per_vb(self.estimate_fee(target).await?)
+	}
+
+	async fn send_transaction<S>(&self, tx: S) -> Result<Txid>
+	where
+		S: Send + Sync + AsRef<str>,
+	{
+		self.broadcast(tx).await
+	}
+}
+
+pub(crate) fn btc_per_kvb_to_sat_per_vb(rate: f64) -> Result<u64> {
+	if rate <= 0. {
+		Err(Error::Server { code: 0, message: "no fee estimate available".into() })?;
+	}
+
+	Ok(Amount::from_btc(rate)?.to_sat().div_ceil(1_000))
+}
+#[test]
+fn btc_per_kvb_to_sat_per_vb_should_work() {
+	assert_eq!(btc_per_kvb_to_sat_per_vb(0.00001).unwrap(), 1);
+	assert_eq!(btc_per_kvb_to_sat_per_vb(0.00025001).unwrap(), 26);
+	assert!(btc_per_kvb_to_sat_per_vb(-1.).is_err());
+}
+
+/// Wait until the chain backend finds a UTXO worth at least `satoshis` which the Atomicals
+/// index lists without any atomical.
+///
+/// A backend without an Atomicals index can't tell a plain UTXO from one carrying atomicals, and
+/// spending the latter as funding burns them. So every candidate is looked up in the index, and
+/// one the index doesn't know yet is skipped until it does.
+pub async fn wait_for_plain_utxo<C, A>(
+	chain: &C,
+	index: &A,
+	address: &str,
+	satoshis: u64,
+	timeout: Option<Duration>,
+) -> Result<Utxo>
+where
+	C: Chain,
+	A: Api,
+{
+	poll_utxo(chain, address, satoshis, timeout, |us| async move {
+		let indexed = index.get_unspent_address(address).await?;
+
+		Ok(us
+			.into_iter()
+			.filter(|u| match indexed.iter().find(|i| i.txid == u.txid && i.vout == u.vout) {
+				Some(i) if i.is_plain() => true,
+				Some(i) => {
+					tracing::warn!(
+						"skipping UTXO {}:{} which carries atomicals {:?}",
+						u.txid,
+						u.vout,
+						i.atomicals
+					);
+
+					false
+				},
+				None => {
+					tracing::info!("skipping UTXO {}:{} which is not indexed yet", u.txid, u.vout);
+
+					false
+				},
+			})
+			.collect())
+	})
+	.await
+}
+
+// Poll for a UTXO, for backends which cannot push notifications.
+//
+// The candidates go through `check` before one is taken.
+pub(crate) async fn poll_utxo<C, F, Fut>(
+	chain: &C,
+	address: &str,
+	satoshis: u64,
+	timeout: Option<Duration>,
+	check: F,
+) -> Result<Utxo>
+where
+	C: Chain,
+	F: Fn(Vec<Utxo>) -> Fut,
+	Fut: Future<Output = Result<Vec<Utxo>>>,
+{
+	let deadline = timeout.map(|t| Instant::now() + t);
+
+	loop {
+		let candidates = chain
+			.utxos(address)
+			.await?
+			.into_iter()
+			.filter(|u| u.is_plain() && u.value >= satoshis)
+			.collect::<Vec<_>>();
+		let found =
+			if candidates.is_empty() { None } else { check(candidates).await?.into_iter().next() };
+
+		if let Some(u) = found {
+			tracing::info!(
+				"funding UTXO detected {}:{} with a value of {} for funding purposes",
+				u.txid,
+				u.vout,
+				u.value
+			);
+
+			return Ok(u);
+		}
+
+		let mut period = Duration::from_secs(5);
+
+		if let Some(d) = deadline {
+			let left = d.saturating_duration_since(Instant::now());
+
+			if left.is_zero() {
+				Err(Error::WaitTimeout { address: address.into(), satoshis })?;
+			}
+
+			period = period.min(left);
+		}
+
+		tracing::info!(
+			"awaiting UTXO confirmation until {} BTC is received at address {address}",
+			Amount::from_sat(satoshis)
+		);
+
+		time::sleep(period).await;
+	}
+}
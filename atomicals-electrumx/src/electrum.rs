@@ -0,0 +1,1100 @@
+//! Native Electrum protocol client.
+//!
+//! Talks newline-delimited JSON-RPC to an ElectrumX server over TCP (`tcp://HOST:PORT`) or TLS
+//! (`ssl://HOST:PORT`) instead of going through the HTTP proxy.
+
+// std
+use std::{
+	collections::HashMap,
+	fmt::{Debug, Formatter, Result as FmtResult},
+	fs,
+	sync::{
+		atomic::{AtomicBool, AtomicU64, Ordering},
+		Arc, Mutex as StdMutex,
+	},
+	time::Duration,
+};
+// crates.io
+use bitcoin::Network;
+use reqwest::Url;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use tokio::{
+	io::{self, AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader, WriteHalf},
+	net::{self, TcpStream},
+	sync::{
+		broadcast::{self, error::RecvError},
+		oneshot, Mutex,
+	},
+	task::JoinHandle,
+	time::{self, Instant},
+};
+use tokio_rustls::{
+	rustls::{ClientConfig, OwnedTrustAnchor, RootCertStore, ServerName},
+	TlsConnector,
+};
+use tokio_socks::{tcp::Socks5Stream, TargetAddr};
+// atomicals-electrumx
+use crate::{
+	prelude::*,
+	retry,
+	transport::{HttpOptions, RateLimiter},
+	Config, Http, MaxRetries,
+};
+
+const HEADERS_SUBSCRIBE: &str = "blockchain.headers.subscribe";
+const SCRIPTHASH_SUBSCRIBE: &str = "blockchain.scripthash.subscribe";
+
+type Pending = Arc<StdMutex<HashMap<u64, oneshot::Sender<StdResult<Value, ServerError>>>>>;
+
+trait Stream: AsyncRead + AsyncWrite + Send + Unpin {}
+impl<T> Stream for T where T: AsyncRead + AsyncWrite + Send + Unpin {}
+
+/// Electrum protocol client.
+///
+/// The connection is established lazily and re-established after it drops. Requests are
+/// pipelined over it and matched to their responses by id.
+pub struct Electrum {
+	/// Request timeout.
+	pub timeout: Duration,
+	/// Retry period.
+	pub retry_period: Duration,
+	/// Maximum number of retry attempts.
+	pub max_retries: MaxRetries,
+	/// Network type.
+	pub network: Network,
+	/// Base URI.
+	pub base_uri: String,
+	/// Client name sent in `server.version`.
+	pub client_name: String,
+	/// Protocol version sent in `server.version`.
+	pub protocol_version: String,
+	/// Maximum time to wait for a UTXO.
+	pub wait_timeout: Option<Duration>,
+	/// Client side rate limiter.
+	pub rate_limiter: Option<RateLimiter>,
+	proxy: Option<Socks>,
+	tls: Arc<ClientConfig>,
+	connection: Mutex<Option<Arc<Connection>>>,
+}
+impl Electrum {
+	/// Make a request at `server.version` and return the server software and the negotiated
+	/// protocol version.
+	pub async fn server_version(&self) -> Result<(String, String)> {
+		Ok(self.connection().await?.server_version.clone())
+	}
+
+	/// Send the calls in a single batch.
+	///
+	/// The results are returned in the order of the calls.
+	pub async fn batch<M, P>(&self, calls: Vec<(M, P)>) -> Result<Vec<Result<Value>>>
+	where
+		M: AsRef<str>,
+		P: Serialize,
+	{
+		let calls = calls
+			.into_iter()
+			.map(|(m, p)| Ok((m.as_ref().to_owned(), serde_json::to_value(p)?)))
+			.collect::<Result<Vec<_>>>()?;
+
+		let calls = &calls;
+		let rs = retry(&self.max_retries, self.retry_period, &self.base_uri, || async move {
+			let c = self.connection().await?;
+
+			self.throttle().await;
+			self.reset_on_error(&c, c.batch(calls, self.timeout).await).await
+		})
+		.await?;
+
+		Ok(rs.into_iter().map(|r| r.map_err(Into::into)).collect())
+	}
+
+	async fn call(&self, method: &str, params: &Value) -> Result<Value> {
+		retry(&self.max_retries, self.retry_period, method, || async move {
+			let c = self.connection().await?;
+
+			self.throttle().await;
+
+			Ok(self.reset_on_error(&c, c.call(method, params, self.timeout).await).await??)
+		})
+		.await
+	}
+
+	async fn throttle(&self) {
+		if let Some(l) = &self.rate_limiter {
+			l.acquire().await;
+		}
+	}
+
+	// Drop the connection once it fails, the next request reconnects.
+	async fn reset_on_error<T>(&self, connection: &Arc<Connection>, r: Result<T>) -> Result<T> {
+		if r.is_err() {
+			let mut c = self.connection.lock().await;
+
+			if c.as_ref().is_some_and(|c| Arc::ptr_eq(c, connection)) {
+				c.take();
+			}
+		}
+
+		r
+	}
+
+	async fn connection(&self) -> Result<Arc<Connection>> {
+		let mut c = self.connection.lock().await;
+
+		if let Some(c) = c.as_ref().filter(|c| !c.closed.load(Ordering::SeqCst)) {
+			return Ok(c.clone());
+		}
+
+		let new = Arc::new(
+			time::timeout(self.timeout, Connection::open(self))
+				.await
+				.map_err(|_| Error::Timeout)??,
+		);
+
+		tracing::info!(
+			"connected to {} running {} with protocol {}",
+			self.base_uri,
+			new.server_version.0,
+			new.server_version.1
+		);
+
+		*c = Some(new.clone());
+
+		Ok(new)
+	}
+}
+impl Config for Electrum {
+	fn network(&self) -> &Network {
+		&self.network
+	}
+
+	fn base_uri(&self) -> &str {
+		&self.base_uri
+	}
+
+	fn wait_timeout(&self) -> Option<Duration> {
+		self.wait_timeout
+	}
+}
+impl Http for Electrum {
+	async fn post<U, P, R>(&self, uri: U, params: P) -> Result<R>
+	where
+		U: Send + Sync + AsRef<str>,
+		P: Send + Sync + Serialize,
+		R: DeserializeOwned,
+	{
+		// `Api` addresses methods as `BASE_URI/METHOD`.
+		let u = uri.as_ref();
+		let method = u.rsplit_once('/').map(|(_, m)| m).unwrap_or(u);
+		let r = self.call(method, &serde_json::to_value(params)?).await?;
+
+		envelope(r)
+	}
+
+	async fn post_batch<U, P, R>(&self, requests: Vec<(U, P)>) -> Result<Vec<Result<R>>>
+	where
+		U: Send + Sync + AsRef<str>,
+		P: Send + Sync + Serialize,
+		R: Send + DeserializeOwned,
+	{
+		let calls = requests
+			.iter()
+			.map(|(u, p)| {
+				let u = u.as_ref();
+
+				(u.rsplit_once('/').map(|(_, m)| m).unwrap_or(u), p)
+			})
+			.collect();
+
+		Ok(self.batch(calls).await?.into_iter().map(|r| envelope(r?)).collect())
+	}
+
+	async fn subscribe<S>(&self, scripthash: S) -> Result<Option<Subscription>>
+	where
+		S: Send + Sync + AsRef<str>,
+	{
+		let sh = scripthash.as_ref();
+		let c = self.connection().await?;
+		// Listen before subscribing so that no notification slips through.
+		let receiver = c.notifications.resubscribe();
+
+		// Subscribing again is harmless, the server keeps one subscription per key.
+		for (m, p) in [
+			(HEADERS_SUBSCRIBE, serde_json::json!([])),
+			(SCRIPTHASH_SUBSCRIBE, serde_json::json!([sh])),
+		] {
+			c.call(m, &p, self.timeout).await??;
+		}
+
+		Ok(Some(Subscription { scripthash: sh.into(), receiver }))
+	}
+}
+impl Debug for Electrum {
+	fn fmt(&self, f: &mut Formatter) -> FmtResult {
+		f.debug_struct("Electrum")
+			.field("timeout", &self.timeout)
+			.field("retry_period", &self.retry_period)
+			.field("max_retries", &self.max_retries)
+			.field("network", &self.network)
+			.field("base_uri", &self.base_uri)
+			.field("client_name", &self.client_name)
+			.field("protocol_version", &self.protocol_version)
+			.field("wait_timeout", &self.wait_timeout)
+			.field("rate_limiter", &self.rate_limiter)
+			.field("proxy", &self.proxy)
+			.finish()
+	}
+}
+
+/// Builder for [`Electrum`].
+#[derive(Debug)]
+pub struct ElectrumBuilder {
+	/// Request timeout.
+	pub timeout: Duration,
+	/// Retry period.
+	pub retry_period: Duration,
+	/// Maximum number of retry attempts.
+	pub max_retries: MaxRetries,
+	/// Network type.
+	pub network: Network,
+	/// Base URI.
+	pub base_uri: String,
+	/// Client name sent in `server.version`.
+	pub client_name: String,
+	/// Protocol version sent in `server.version`.
+	pub protocol_version: String,
+	/// Maximum time to wait for a UTXO.
+	pub wait_timeout: Option<Duration>,
+	/// Settings of the connection, see [`ElectrumBuilder::http`].
+	pub http: HttpOptions,
+}
+impl ElectrumBuilder {
+	/// Set request timeout.
+	pub fn timeout(mut self, timeout: Duration) -> Self {
+		self.timeout = timeout;
+
+		self
+	}
+
+	/// Set retry period.
+	pub fn retry_period(mut self, retry_period: Duration) -> Self {
+		self.retry_period = retry_period;
+
+		self
+	}
+
+	/// Set maximum number of retry attempts.
+	pub fn max_retries(mut self, max_retries: MaxRetries) -> Self {
+		self.max_retries = max_retries;
+
+		self
+	}
+
+	/// Set network type.
+	pub fn network(mut self, network: Network) -> Self {
+		self.network = network;
+
+		self
+	}
+
+	/// Set base URI.
+	pub fn base_uri<S>(mut self, base_uri: S) -> Self
+	where
+		S: Into<String>,
+	{
+		self.base_uri = base_uri.into();
+
+		self
+	}
+
+	/// Set client name.
+	pub fn client_name<S>(mut self, client_name: S) -> Self
+	where
+		S: Into<String>,
+	{
+		self.client_name = client_name.into();
+
+		self
+	}
+
+	/// Set protocol version.
+	pub fn protocol_version<S>(mut self, protocol_version: S) -> Self
+	where
+		S: Into<String>,
+	{
+		self.protocol_version = protocol_version.into();
+
+		self
+	}
+
+	/// Set maximum time to wait for a UTXO.
+	pub fn wait_timeout(mut self, wait_timeout: Option<Duration>) -> Self {
+		self.wait_timeout = wait_timeout;
+
+		self
+	}
+
+	/// Set the settings of the connection.
+	///
+	/// The proxy must be a SOCKS5 one, the root certificates are trusted on top of the bundled
+	/// ones and the rate limit applies to the requests. There are no headers to send.
+	pub fn http(mut self, http: HttpOptions) -> Self {
+		self.http = http;
+
+		self
+	}
+
+	/// Build the [`Electrum`] client.
+	pub fn build(self) -> Result<Electrum> {
+		Endpoint::parse(&self.base_uri)?;
+
+		if !self.http.headers.is_empty() {
+			Err(Error::Unsupported("HTTP headers".into()))?;
+		}
+
+		let proxy = self.http.proxy.as_deref().map(Socks::parse).transpose()?;
+		let mut roots = RootCertStore::empty();
+
+		roots.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|a| {
+			OwnedTrustAnchor::from_subject_spki_name_constraints(
+				a.subject,
+				a.spki,
+				a.name_constraints,
+			)
+		}));
+
+		for p in &self.http.ca_certificates {
+			roots.add_parsable_certificates(&rustls_pemfile::certs(&mut &*fs::read(p)?)?);
+		}
+
+		let tls = ClientConfig::builder()
+			.with_safe_defaults()
+			.with_root_certificates(roots)
+			.with_no_client_auth();
+
+		Ok(Electrum {
+			timeout: self.timeout,
+			retry_period: self.retry_period,
+			max_retries: self.max_retries,
+			network: self.network,
+			base_uri: self.base_uri,
+			client_name: self.client_name,
+			protocol_version: self.protocol_version,
+			wait_timeout: self.wait_timeout,
+			rate_limiter: self.http.rate_limiter(),
+			proxy,
+			tls: Arc::new(tls),
+			connection: Mutex::new(None),
+		})
+	}
+}
+impl Default for ElectrumBuilder {
+	fn default() -> Self {
+		Self {
+			timeout: Duration::from_secs(30),
+			retry_period: Duration::from_secs(5),
+			max_retries: MaxRetries::Finite(5),
+			network: Network::Bitcoin,
+			base_uri: "tcp://127.0.0.1:50001".into(),
+			client_name: concat!("atomicals-electrumx/", env!("CARGO_PKG_VERSION")).into(),
+			protocol_version: "1.4".into(),
+			wait_timeout: None,
+			http: HttpOptions::default(),
+		}
+	}
+}
+
+// Mirror the proxy's response envelope.
+fn envelope<R>(response: Value) -> Result<R>
+where
+	R: DeserializeOwned,
+{
+	Ok(serde_json::from_value(serde_json::json!({ "success": true, "response": response }))?)
+}
+
+/// Check if the URI addresses an Electrum server rather than an HTTP proxy.
+pub fn is_electrum_uri(uri: &str) -> bool {
+	uri.starts_with("tcp://") || uri.starts_with("ssl://")
+}
+
+// SOCKS5 proxy to reach the server through.
+#[derive(Debug, PartialEq, Eq)]
+struct Socks {
+	addr: String,
+	// Let the proxy resolve the host, as `socks5h` asks.
+	remote_dns: bool,
+	auth: Option<(String, String)>,
+}
+impl Socks {
+	fn parse(proxy: &str) -> Result<Self> {
+		let invalid = || Error::InvalidProxy(proxy.into());
+		let u = Url::parse(proxy).map_err(|_| invalid())?;
+		let remote_dns = match u.scheme() {
+			"socks5" => false,
+			"socks5h" => true,
+			_ => Err(invalid())?,
+		};
+		let host = u.host_str().ok_or_else(invalid)?;
+		let auth = (!u.username().is_empty())
+			.then(|| (u.username().to_owned(), u.password().unwrap_or_default().to_owned()));
+
+		Ok(Self { addr: format!("{host}:{}", u.port().unwrap_or(1080)), remote_dns, auth })
+	}
+
+	async fn connect(&self, host: &str, port: u16) -> Result<TcpStream> {
+		let target = if self.remote_dns {
+			TargetAddr::Domain(host.to_owned().into(), port)
+		} else {
+			TargetAddr::Ip(net::lookup_host((host, port)).await?.next().ok_or_else(|| {
+				io::Error::new(io::ErrorKind::NotFound, format!("failed to resolve {host}"))
+			})?)
+		};
+		let s = match &self.auth {
+			Some((user, password)) =>
+				Socks5Stream::connect_with_password(self.addr.as_str(), target, user, password)
+					.await?,
+			None => Socks5Stream::connect(self.addr.as_str(), target).await?,
+		};
+
+		Ok(s.into_inner())
+	}
+}
+#[test]
+fn socks_should_work() {
+	assert_eq!(
+		Socks::parse("socks5h://127.0.0.1:9050").unwrap(),
+		Socks { addr: "127.0.0.1:9050".into(), remote_dns: true, auth: None }
+	);
+	assert_eq!(
+		Socks::parse("socks5://u:p@localhost").unwrap(),
+		Socks {
+			addr: "localhost:1080".into(),
+			remote_dns: false,
+			auth: Some(("u".into(), "p".into()))
+		}
+	);
+	assert!(matches!(Socks::parse("http://127.0.0.1:8080"), Err(Error::InvalidProxy(_))));
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Endpoint<'a> {
+	tls: bool,
+	host: &'a str,
+	port: u16,
+}
+impl<'a> Endpoint<'a> {
+	fn parse(uri: &'a str) -> Result<Self> {
+		let invalid = || Error::InvalidUri(uri.into());
+		let (tls, rest) = if let Some(r) = uri.strip_prefix("tcp://") {
+			(false, r)
+		} else if let Some(r) = uri.strip_prefix("ssl://") {
+			(true, r)
+		} else {
+			Err(invalid())?
+		};
+		let (host, port) = rest.trim_end_matches('/').rsplit_once(':').ok_or_else(invalid)?;
+
+		if host.is_empty() {
+			Err(invalid())?;
+		}
+
+		Ok(Self { tls, host, port: port.parse().map_err(|_| invalid())? })
+	}
+}
+#[test]
+fn endpoint_parse_should_work() {
+	assert_eq!(
+		Endpoint::parse("tcp://127.0.0.1:50001").unwrap(),
+		Endpoint { tls: false, host: "127.0.0.1", port: 50001 }
+	);
+	assert_eq!(
+		Endpoint::parse("ssl://ep.atomicals.xyz:50002/").unwrap(),
+		Endpoint { tls: true, host: "ep.atomicals.xyz", port: 50002 }
+	);
+	assert!(Endpoint::parse("https://ep.atomicals.xyz/proxy").is_err());
+	assert!(Endpoint::parse("tcp://127.0.0.1").is_err());
+	assert!(Endpoint::parse("tcp://:50001").is_err());
+}
+
+/// Notifications of a scripthash and the chain headers.
+#[derive(Debug)]
+pub struct Subscription {
+	scripthash: String,
+	receiver: broadcast::Receiver<Notification>,
+}
+impl Subscription {
+	/// Wait until the status of the scripthash changes or a new block arrives.
+	///
+	/// Return `false` if nothing happened within the timeout.
+	pub async fn changed(&mut self, timeout: Duration) -> Result<bool> {
+		let deadline = Instant::now() + timeout;
+
+		loop {
+			match time::timeout_at(deadline, self.receiver.recv()).await {
+				Err(_) => return Ok(false),
+				Ok(Ok(n)) =>
+					if n.method == HEADERS_SUBSCRIBE
+						|| (n.method == SCRIPTHASH_SUBSCRIBE && n.params[0] == *self.scripthash)
+					{
+						return Ok(true);
+					},
+				// Some notifications were dropped, one of them might be ours.
+				Ok(Err(RecvError::Lagged(_))) => return Ok(true),
+				Ok(Err(RecvError::Closed)) => Err(Error::ConnectionClosed)?,
+			}
+		}
+	}
+}
+
+#[derive(Clone, Debug)]
+struct Notification {
+	method: String,
+	params: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct ServerError {
+	code: i64,
+	message: String,
+}
+impl From<ServerError> for Error {
+	fn from(e: ServerError) -> Self {
+		Self::Server { code: e.code, message: e.message }
+	}
+}
+
+#[derive(Debug, Deserialize)]
+struct Message {
+	id: Option<u64>,
+	#[serde(default)]
+	result: Value,
+	error: Option<ServerError>,
+	method: Option<String>,
+	#[serde(default)]
+	params: Value,
+}
+
+struct Connection {
+	writer: Mutex<WriteHalf<Box<dyn Stream>>>,
+	pending: Pending,
+	closed: Arc<AtomicBool>,
+	next_id: AtomicU64,
+	reader: JoinHandle<()>,
+	notifications: broadcast::Receiver<Notification>,
+	server_version: (String, String),
+}
+impl Connection {
+	async fn open(electrum: &Electrum) -> Result<Self> {
+		let uri = &electrum.base_uri;
+		let e = Endpoint::parse(uri)?;
+		let tcp = match &electrum.proxy {
+			Some(p) => p.connect(e.host, e.port).await?,
+			None => TcpStream::connect((e.host, e.port)).await?,
+		};
+
+		tcp.set_nodelay(true)?;
+
+		let stream: Box<dyn Stream> = if e.tls {
+			let name = ServerName::try_from(e.host).map_err(|_| Error::InvalidUri(uri.clone()))?;
+
+			Box::new(TlsConnector::from(electrum.tls.clone()).connect(name, tcp).await?)
+		} else {
+			Box::new(tcp)
+		};
+		let (r, w) = io::split(stream);
+		let pending = Pending::default();
+		let closed = Arc::new(AtomicBool::new(false));
+		// The reader owns the only sender, so listeners learn when the connection is gone.
+		let (tx, notifications) = broadcast::channel(64);
+		let reader =
+			tokio::spawn(Self::read(BufReader::new(r), pending.clone(), closed.clone(), tx));
+		let mut c = Self {
+			writer: Mutex::new(w),
+			pending,
+			closed,
+			next_id: AtomicU64::new(0),
+			reader,
+			notifications,
+			server_version: Default::default(),
+		};
+		let v = c
+			.call(
+				"server.version",
+				&serde_json::json!([electrum.client_name, electrum.protocol_version]),
+				electrum.timeout,
+			)
+			.await??;
+
+		c.server_version = serde_json::from_value(v)?;
+
+		Ok(c)
+	}
+
+	async fn read<R>(
+		mut reader: BufReader<R>,
+		pending: Pending,
+		closed: Arc<AtomicBool>,
+		notifications: broadcast::Sender<Notification>,
+	) where
+		R: AsyncRead + Unpin,
+	{
+		let mut line = String::new();
+
+		loop {
+			line.clear();
+
+			match reader.read_line(&mut line).await {
+				Ok(0) => break,
+				Ok(_) => {},
+				Err(e) => {
+					tracing::error!("failed to read from the connection due to {e}");
+
+					break;
+				},
+			}
+
+			// A batch is answered with an array, a single request with an object.
+			let ms = match serde_json::from_str::<Value>(&line) {
+				Ok(Value::Array(ms)) => ms,
+				Ok(m) => vec![m],
+				Err(e) => {
+					tracing::error!("failed to parse message {line:?} due to {e}");
+
+					continue;
+				},
+			};
+
+			for m in ms {
+				match serde_json::from_value::<Message>(m) {
+					Ok(Message { id: Some(id), result, error, .. }) => {
+						if let Some(tx) = pending.lock().unwrap().remove(&id) {
+							let _ = tx.send(error.map_or(Ok(result), Err));
+						}
+					},
+					Ok(Message { method: Some(method), params, .. }) => {
+						// No one listening is fine.
+						let _ = notifications.send(Notification { method, params });
+					},
+					Ok(m) => tracing::warn!("ignored unexpected message {m:?}"),
+					Err(e) => tracing::error!("failed to parse message due to {e}"),
+				}
+			}
+		}
+
+		closed.store(true, Ordering::SeqCst);
+		// Dropping the senders wakes up every request still waiting on this connection.
+		pending.lock().unwrap().clear();
+	}
+
+	fn request(
+		&self,
+		method: &str,
+		params: &Value,
+	) -> (Value, (u64, oneshot::Receiver<StdResult<Value, ServerError>>)) {
+		let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+		let (tx, rx) = oneshot::channel();
+
+		self.pending.lock().unwrap().insert(id, tx);
+
+		(
+			serde_json::json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }),
+			(id, rx),
+		)
+	}
+
+	// Stop waiting for the responses, a late one is then dropped by the reader.
+	fn forget<I>(&self, ids: I)
+	where
+		I: IntoIterator<Item = u64>,
+	{
+		let mut p = self.pending.lock().unwrap();
+
+		for id in ids {
+			p.remove(&id);
+		}
+	}
+
+	async fn send(&self, message: &Value) -> Result<()> {
+		let mut bytes = serde_json::to_vec(message)?;
+
+		bytes.push(b'\n');
+
+		let mut w = self.writer.lock().await;
+
+		w.write_all(&bytes).await?;
+		w.flush().await?;
+
+		Ok(())
+	}
+
+	async fn call(
+		&self,
+		method: &str,
+		params: &Value,
+		timeout: Duration,
+	) -> Result<StdResult<Value, ServerError>> {
+		let deadline = Instant::now() + timeout;
+		let (req, (id, rx)) = self.request(method, params);
+
+		if let Err(e) = self.send(&req).await {
+			self.forget([id]);
+
+			Err(e)?;
+		}
+
+		self.receive(id, rx, deadline).await
+	}
+
+	async fn batch(
+		&self,
+		calls: &[(String, Value)],
+		timeout: Duration,
+	) -> Result<Vec<StdResult<Value, ServerError>>> {
+		// The whole batch shares one deadline.
+		let deadline = Instant::now() + timeout;
+		let (reqs, rxs) =
+			calls.iter().map(|(m, p)| self.request(m, p)).unzip::<_, _, Vec<_>, Vec<_>>();
+		let ids = rxs.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+
+		if let Err(e) = self.send(&Value::Array(reqs)).await {
+			self.forget(ids.iter().copied());
+
+			Err(e)?;
+		}
+
+		let mut rs = Vec::with_capacity(rxs.len());
+
+		for (i, (id, rx)) in rxs.into_iter().enumerate() {
+			match self.receive(id, rx, deadline).await {
+				Ok(r) => rs.push(r),
+				Err(e) => {
+					self.forget(ids[i..].iter().copied());
+
+					Err(e)?;
+				},
+			}
+		}
+
+		Ok(rs)
+	}
+
+	async fn receive(
+		&self,
+		id: u64,
+		rx: oneshot::Receiver<StdResult<Value, ServerError>>,
+		deadline: Instant,
+	) -> Result<StdResult<Value, ServerError>> {
+		match time::timeout_at(deadline, rx).await {
+			Ok(r) => r.map_err(|_| Error::ConnectionClosed),
+			Err(_) => {
+				self.forget([id]);
+
+				Err(Error::Timeout)
+			},
+		}
+	}
+}
+impl Drop for Connection {
+	fn drop(&mut self) {
+		self.reader.abort();
+	}
+}
+
+#[cfg(test)]
+async fn mock() -> String {
+	mock_counted().await.0
+}
+// Also count the round trips, a batch being one.
+#[cfg(test)]
+pub(crate) async fn mock_counted() -> (String, Arc<AtomicU64>) {
+	// crates.io
+	use tokio::net::{tcp::OwnedWriteHalf, TcpListener};
+
+	let l = TcpListener::bind("127.0.0.1:0").await.unwrap();
+	let uri = format!("tcp://{}", l.local_addr().unwrap());
+	let round_trips = Arc::new(AtomicU64::new(0));
+	let counter = round_trips.clone();
+
+	tokio::spawn(async move {
+		loop {
+			let (s, _) = l.accept().await.unwrap();
+			let counter = counter.clone();
+
+			tokio::spawn(async move {
+				let (r, w) = s.into_split();
+				let w = Arc::new(Mutex::new(w));
+				let funded = Arc::new(AtomicBool::new(false));
+				let mut lines = BufReader::new(r).lines();
+				let notify = |w: Arc<Mutex<OwnedWriteHalf>>, method: &str, params: Value| {
+					let n =
+						serde_json::json!({ "jsonrpc": "2.0", "method": method, "params": params });
+
+					async move {
+						let mut w = w.lock().await;
+						let _ = w.write_all(format!("{n}\n").as_bytes()).await;
+					}
+				};
+
+				while let Some(l) = lines.next_line().await.unwrap() {
+					counter.fetch_add(1, Ordering::SeqCst);
+
+					let answer = |m: &Value| {
+						let id = m["id"].clone();
+						let r = match m["method"].as_str().unwrap() {
+							"server.version" => serde_json::json!(["MockX 1.0", m["params"][1]]),
+							HEADERS_SUBSCRIBE => serde_json::json!({ "height": 1, "hex": "" }),
+							SCRIPTHASH_SUBSCRIBE => Value::Null,
+							"blockchain.scripthash.listunspent"
+								if funded.load(Ordering::SeqCst) =>
+								serde_json::json!([{
+									"txid": "00",
+									"tx_hash": "00",
+									"index": 0,
+									"tx_pos": 0,
+									"vout": 0,
+									"height": 0,
+									"value": 1000,
+									"atomicals": []
+								}]),
+							"blockchain.scripthash.listunspent" => serde_json::json!([]),
+							"error" =>
+								return serde_json::json!({
+									"jsonrpc": "2.0",
+									"id": id,
+									"error": { "code": 1, "message": "bad request" }
+								}),
+							_ => m["params"][0].clone(),
+						};
+
+						serde_json::json!({ "jsonrpc": "2.0", "id": id, "result": r })
+					};
+					let m = serde_json::from_str::<Value>(&l).unwrap();
+					let (delay, res) = match &m {
+						Value::Array(ms) => (
+							false,
+							Value::Array(
+								ms.iter().filter(|m| m["method"] != "hang").map(answer).collect(),
+							),
+						),
+						m if m["method"] == "close" => return,
+						m if m["method"] == "hang" => continue,
+						m => (m["method"] == "slow", answer(m)),
+					};
+					let w = w.clone();
+					let funded = funded.clone();
+
+					// Answer slow requests later to check responses are matched out of order.
+					tokio::spawn(async move {
+						if delay {
+							time::sleep(Duration::from_millis(200)).await;
+						}
+
+						w.lock().await.write_all(format!("{res}\n").as_bytes()).await.unwrap();
+						// Interleave notifications of another scripthash with the responses.
+						notify(w.clone(), SCRIPTHASH_SUBSCRIBE, serde_json::json!(["ff", "ff"]))
+							.await;
+
+						// Fund the subscribed scripthash a bit later.
+						if m["method"] == SCRIPTHASH_SUBSCRIBE {
+							time::sleep(Duration::from_millis(200)).await;
+							funded.store(true, Ordering::SeqCst);
+							notify(
+								w,
+								SCRIPTHASH_SUBSCRIBE,
+								serde_json::json!([m["params"][0], "00"]),
+							)
+							.await;
+						}
+					});
+				}
+			});
+		}
+	});
+
+	(uri, round_trips)
+}
+#[test]
+fn electrum_should_work() {
+	// crates.io
+	use tokio::runtime::Runtime;
+	// atomicals-electrumx
+	use crate::Api;
+
+	Runtime::new().unwrap().block_on(async {
+		let e = ElectrumBuilder::default()
+			.timeout(Duration::from_secs(5))
+			.retry_period(Duration::from_millis(10))
+			.base_uri(mock().await)
+			.build()
+			.unwrap();
+
+		assert_eq!(e.server_version().await.unwrap(), ("MockX 1.0".into(), "1.4".into()));
+		assert_eq!(e.get_transaction("00").await.unwrap(), "00");
+
+		// Pipelining.
+		let (slow, fast) = tokio::join!(
+			e.post::<_, _, Value>(e.uri_of("slow"), ["slow"]),
+			e.post::<_, _, Value>(e.uri_of("fast"), ["fast"]),
+		);
+
+		assert_eq!(slow.unwrap()["response"], "slow");
+		assert_eq!(fast.unwrap()["response"], "fast");
+
+		// Server errors are not retried.
+		assert!(matches!(
+			e.post::<_, _, Value>(e.uri_of("error"), ()).await,
+			Err(Error::Server { code: 1, .. })
+		));
+
+		// Batching.
+		let rs = e
+			.batch(vec![
+				("a", serde_json::json!([1])),
+				("error", Value::Null),
+				("b", serde_json::json!([2])),
+			])
+			.await
+			.unwrap();
+
+		assert_eq!(rs[0].as_ref().unwrap(), 1);
+		assert!(matches!(rs[1], Err(Error::Server { code: 1, .. })));
+		assert_eq!(rs[2].as_ref().unwrap(), 2);
+
+		let rs = e
+			.post_batch::<_, _, Value>(vec![(e.uri_of("a"), [1]), (e.uri_of("error"), [0])])
+			.await
+			.unwrap();
+
+		assert_eq!(rs[0].as_ref().unwrap()["response"], 1);
+		assert!(matches!(rs[1], Err(Error::Server { code: 1, .. })));
+
+		// Nothing is left pending after a timeout, and a batch waits for one timeout at most.
+		let c = Connection::open(&e).await.unwrap();
+		let t = Duration::from_millis(200);
+
+		assert!(matches!(c.call("hang", &Value::Null, t).await, Err(Error::Timeout)));
+		assert!(c.pending.lock().unwrap().is_empty());
+
+		let now = Instant::now();
+		let hang = ("hang".to_owned(), Value::Null);
+
+		assert!(matches!(
+			c.batch(&[("a".into(), serde_json::json!([1])), hang.clone(), hang], t).await,
+			Err(Error::Timeout)
+		));
+		assert!(now.elapsed() < t * 2);
+		assert!(c.pending.lock().unwrap().is_empty());
+
+		// Reconnect after the server drops the connection.
+		assert!(e.post::<_, _, Value>(e.uri_of("close"), ()).await.is_err());
+		assert_eq!(e.get_transaction("01").await.unwrap(), "01");
+	});
+}
+// Minimal SOCKS5 proxy without authentication, counting the connections it forwards.
+#[cfg(test)]
+async fn socks() -> (String, Arc<AtomicU64>) {
+	// crates.io
+	use tokio::{io::AsyncReadExt, net::TcpListener};
+
+	let l = TcpListener::bind("127.0.0.1:0").await.unwrap();
+	let uri = format!("socks5h://{}", l.local_addr().unwrap());
+	let count = Arc::new(AtomicU64::new(0));
+	let count_ = count.clone();
+
+	tokio::spawn(async move {
+		loop {
+			let (mut s, _) = l.accept().await.unwrap();
+
+			count_.fetch_add(1, Ordering::SeqCst);
+
+			tokio::spawn(async move {
+				let mut b = [0; 2];
+
+				s.read_exact(&mut b).await.unwrap();
+				s.read_exact(&mut vec![0; b[1] as usize]).await.unwrap();
+				s.write_all(&[5, 0]).await.unwrap();
+
+				// The host is left to the proxy to resolve.
+				let mut b = [0; 5];
+
+				s.read_exact(&mut b).await.unwrap();
+
+				assert_eq!(b[3], 3);
+
+				let mut host = vec![0; b[4] as usize];
+
+				s.read_exact(&mut host).await.unwrap();
+
+				let port = s.read_u16().await.unwrap();
+				let mut t =
+					TcpStream::connect((String::from_utf8(host).unwrap(), port)).await.unwrap();
+
+				s.write_all(&[5, 0, 0, 1, 0, 0, 0, 0, 0, 0]).await.unwrap();
+
+				let _ = io::copy_bidirectional(&mut s, &mut t).await;
+			});
+		}
+	});
+
+	(uri, count)
+}
+#[test]
+fn electrum_proxy_should_work() {
+	// crates.io
+	use tokio::runtime::Runtime;
+	// atomicals-electrumx
+	use crate::Api;
+
+	Runtime::new().unwrap().block_on(async {
+		let (proxy, count) = socks().await;
+		let e = ElectrumBuilder::default()
+			.base_uri(mock().await)
+			.http(HttpOptions::default().proxy(proxy))
+			.build()
+			.unwrap();
+
+		assert_eq!(e.get_transaction("00").await.unwrap(), "00");
+		assert_eq!(count.load(Ordering::SeqCst), 1);
+		assert!(matches!(
+			ElectrumBuilder::default().http(HttpOptions::default().header("a", "b")).build(),
+			Err(Error::Unsupported(_))
+		));
+		assert!(matches!(
+			ElectrumBuilder::default()
+				.http(HttpOptions::default().proxy("http://127.0.0.1:8080"))
+				.build(),
+			Err(Error::InvalidProxy(_))
+		));
+	});
+}
+#[test]
+fn wait_until_utxo_should_work() {
+	// crates.io
+	use tokio::runtime::Runtime;
+	// atomicals-electrumx
+	use crate::Api;
+
+	let a = "bc1pqkq0rg5yjrx6u08nhmc652s33g96jmdz4gjp9d46ew6ahun7xuvqaerzsp";
+
+	Runtime::new().unwrap().block_on(async {
+		let e = ElectrumBuilder::default()
+			.wait_timeout(Some(Duration::from_secs(5)))
+			.base_uri(mock().await)
+			.build()
+			.unwrap();
+		let now = Instant::now();
+
+		// The notification wakes the wait up, way earlier than the fallback re-check.
+		assert_eq!(e.wait_until_utxo(a, 1000).await.unwrap().value, 1000);
+		assert!(now.elapsed() < Duration::from_secs(5));
+
+		let e = ElectrumBuilder::default()
+			.wait_timeout(Some(Duration::from_millis(500)))
+			.base_uri(mock().await)
+			.build()
+			.unwrap();
+
+		assert!(matches!(e.wait_until_utxo(a, 2000).await, Err(Error::WaitTimeout { .. })));
+	});
+}
@@ -7,11 +7,180 @@ use thiserror::Error as ThisError;
 
 #[derive(Debug, ThisError)]
 pub enum Error {
-	#[error("exceeded maximum retries")]
-	ExceededMaximumRetries,
+	#[error("exceeded maximum retries, the last error is {0}")]
+	ExceededMaximumRetries(Box<Error>),
+	#[error("invalid electrum URI {0}, expected `tcp://HOST:PORT` or `ssl://HOST:PORT`")]
+	InvalidUri(String),
+	#[error("connection closed by the server")]
+	ConnectionClosed,
+	#[error("request timed out")]
+	Timeout,
+	#[error("the pool has no endpoint")]
+	EmptyPool,
+	#[error("the endpoints of the pool are on different networks")]
+	NetworkMismatch,
+	#[error("endpoint {uri} is lagging at height {height} of {best}")]
+	Lagging { uri: String, height: u64, best: u64 },
+	#[error("timed out waiting for a UTXO of {satoshis} satoshis at {address}")]
+	WaitTimeout { address: String, satoshis: u64 },
+	#[error("timed out waiting for output {0}")]
+	TxOutWaitTimeout(String),
+	#[error("invalid atomical ID {0}, expected `TXIDiINDEX`")]
+	InvalidAtomicalId(String),
+	#[error("invalid cookie file {0:?}, expected `USER:PASSWORD`")]
+	InvalidCookie(std::path::PathBuf),
+	#[error("invalid header {0}")]
+	InvalidHeader(String),
+	#[error(
+		"invalid proxy {0}, an electrum server is only reached through `socks5://` or `socks5h://`"
+	)]
+	InvalidProxy(String),
+	#[error("{0} not supported by electrum servers")]
+	Unsupported(String),
+	#[error("HTTP status {status} with body {body}")]
+	Status { status: u16, body: String },
+	#[error("rate limited, retry after {retry_after:?}")]
+	RateLimited { retry_after: Option<std::time::Duration> },
+	#[error("failed to decode {body} due to {source}")]
+	Decode { source: serde_json::Error, body: String },
+	#[error("server error {code}: {message}")]
+	Server { code: i64, message: String },
+	#[error("transaction rejected due to {reason:?}: {message}")]
+	Rejected { reason: Rejection, message: String },
 
 	#[error(transparent)]
 	Bitcoin(#[from] bitcoin::address::Error),
 	#[error(transparent)]
+	BitcoinAddress(#[from] bitcoin::address::ParseError),
+	#[error(transparent)]
+	BitcoinAmount(#[from] bitcoin::amount::ParseAmountError),
+	#[error(transparent)]
+	BitcoinEncode(#[from] bitcoin::consensus::encode::Error),
+	#[error(transparent)]
+	BitcoinHex(#[from] bitcoin::hex::HexToBytesError),
+	#[error(transparent)]
 	Reqwest(#[from] reqwest::Error),
+	#[error(transparent)]
+	Io(#[from] std::io::Error),
+	#[error(transparent)]
+	SerdeJson(#[from] serde_json::Error),
+	#[error(transparent)]
+	Socks(#[from] tokio_socks::Error),
+}
+/// Reasons for the mempool to reject a transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rejection {
+	/// The fee rate is below the mempool minimum or too low to replace a conflicting transaction.
+	InsufficientFee,
+	/// The transaction has too many unconfirmed ancestors or descendants.
+	TooLongMempoolChain,
+	/// An input is missing or already spent, in the chain or in the mempool.
+	MissingInputs,
+	/// An output is below the dust limit.
+	Dust,
+	/// The transaction violates the standardness policy.
+	NonStandard,
+}
+impl Rejection {
+	/// Classify the rejection message of the node.
+	///
+	/// Return `None` if the message is not a known rejection.
+	pub fn classify(message: &str) -> Option<Self> {
+		let m = message.to_lowercase();
+		let has = |ps: &[&str]| ps.iter().any(|p| m.contains(p));
+
+		if has(&["too-long-mempool-chain"]) {
+			Some(Self::TooLongMempoolChain)
+		} else if has(&[
+			"insufficient fee",
+			"min relay fee not met",
+			"mempool min fee not met",
+			"rejecting replacement",
+		]) {
+			Some(Self::InsufficientFee)
+		} else if has(&["missing-inputs", "missingorspent", "txn-mempool-conflict"]) {
+			Some(Self::MissingInputs)
+		} else if has(&["dust"]) {
+			Some(Self::Dust)
+		} else if has(&["non-standard", "nonstandard", "scriptpubkey", "tx-size"]) {
+			Some(Self::NonStandard)
+		} else {
+			None
+		}
+	}
+
+	/// Check if the message says the transaction is already in the mempool or the chain.
+	pub fn is_already_known(message: &str) -> bool {
+		let m = message.to_lowercase();
+
+		["txn-already-in-mempool", "txn-already-known", "already in block chain"]
+			.iter()
+			.any(|p| m.contains(p))
+	}
+}
+#[test]
+fn classify_should_work() {
+	assert_eq!(
+		Rejection::classify(
+			"the transaction was rejected by network rules.\n\ntoo-long-mempool-chain, too many \
+			 descendants for tx 00 [limit: 25]"
+		),
+		Some(Rejection::TooLongMempoolChain)
+	);
+	assert_eq!(
+		Rejection::classify(
+			"insufficient fee, rejecting replacement 00; new feerate 0.00002 <= old"
+		),
+		Some(Rejection::InsufficientFee)
+	);
+	assert_eq!(
+		Rejection::classify("min relay fee not met, 100 < 141"),
+		Some(Rejection::InsufficientFee)
+	);
+	assert_eq!(
+		Rejection::classify("bad-txns-inputs-missingorspent"),
+		Some(Rejection::MissingInputs)
+	);
+	assert_eq!(Rejection::classify("txn-mempool-conflict"), Some(Rejection::MissingInputs));
+	assert_eq!(Rejection::classify("dust"), Some(Rejection::Dust));
+	assert_eq!(Rejection::classify("scriptpubkey"), Some(Rejection::NonStandard));
+	assert_eq!(Rejection::classify("bad-txns-vout-negative"), None);
+	assert!(Rejection::is_already_known("txn-already-known"));
+	assert!(Rejection::is_already_known("Transaction already in block chain"));
+	assert!(!Rejection::is_already_known("dust"));
+}
+
+impl Error {
+	/// Check if the request might succeed if sent again.
+	pub fn is_retryable(&self) -> bool {
+		match self {
+			Self::ConnectionClosed
+			| Self::Timeout
+			| Self::Lagging { .. }
+			| Self::RateLimited { .. }
+			| Self::Io(_) => true,
+			Self::Reqwest(e) => !e.is_builder(),
+			Self::Status { status, .. } => matches!(status, 408 | 425 | 429 | 500..=599),
+			// The server is busy or failed to reach its daemon.
+			// Bad requests, invalid params and consensus rejections won't change on a retry.
+			Self::Server { code, .. } => matches!(code, 2 | -102 | -32603),
+			_ => false,
+		}
+	}
+}
+#[test]
+fn is_retryable_should_work() {
+	let server = |code| Error::Server { code, message: String::new() };
+	let status = |status| Error::Status { status, body: String::new() };
+
+	assert!(Error::Timeout.is_retryable());
+	assert!(status(502).is_retryable());
+	assert!(status(429).is_retryable());
+	assert!(Error::RateLimited { retry_after: None }.is_retryable());
+	assert!(Error::Lagging { uri: String::new(), height: 9, best: 10 }.is_retryable());
+	assert!(!status(404).is_retryable());
+	assert!(server(2).is_retryable());
+	assert!(!server(1).is_retryable());
+	assert!(!server(-32602).is_retryable());
+	assert!(!Error::ExceededMaximumRetries(Box::new(Error::Timeout)).is_retryable());
 }
@@ -0,0 +1,250 @@
+//! Minimal Esplora REST client, also covering the mempool.space API.
+
+// std
+use std::{collections::HashMap, time::Duration};
+// crates.io
+use bitcoin::Txid;
+use reqwest::{Client as ReqwestClient, ClientBuilder as ReqwestClientBuilder, Response};
+use serde::{de::DeserializeOwned, Deserialize};
+// atomicals-electrumx
+use crate::{
+	chain::{self, Chain},
+	prelude::*,
+	r#type::Utxo,
+	settle_broadcast,
+	transport::{HttpOptions, RateLimiter},
+	txid_of,
+};
+
+/// Esplora REST client.
+///
+/// Esplora knows nothing about atomicals, so [`Utxo::atomicals`] is always empty. Wait for a
+/// funding UTXO with [`chain::wait_for_plain_utxo`] to not burn any.
+#[derive(Debug)]
+pub struct Esplora {
+	/// HTTP client.
+	pub client: ReqwestClient,
+	/// Base URI, e.g. `https://mempool.space/api`.
+	pub uri: String,
+	/// Maximum time to wait for a UTXO, `None` to wait forever.
+	pub wait_timeout: Option<Duration>,
+	/// Client side rate limiter.
+	pub rate_limiter: Option<RateLimiter>,
+}
+impl Esplora {
+	/// Make a request at `GET /address/:address/utxo`.
+	pub async fn address_utxos<S>(&self, address: S) -> Result<Vec<Utxo>>
+	where
+		S: AsRef<str>,
+	{
+		let mut utxos = self
+			.get::<Vec<Unspent>>(&format!("address/{}/utxo", address.as_ref()))
+			.await?
+			.into_iter()
+			.map(|u| Utxo { txid: u.txid, vout: u.vout, value: u.value, atomicals: Vec::new() })
+			.collect::<Vec<_>>();
+
+		utxos.sort_by_key(|u| u.value);
+
+		Ok(utxos)
+	}
+
+	/// Make a request at `GET /tx/:txid/status`.
+	pub async fn tx_status<S>(&self, txid: S) -> Result<TxStatus>
+	where
+		S: AsRef<str>,
+	{
+		self.get(&format!("tx/{}/status", txid.as_ref())).await
+	}
+
+	/// Make a request at `GET /fee-estimates`.
+	///
+	/// Map the confirmation target in blocks to the fee rate in sat/vB.
+	pub async fn fee_estimates(&self) -> Result<HashMap<u16, f64>> {
+		self.get("fee-estimates").await
+	}
+
+	/// Make a request at `POST /tx`.
+	///
+	/// Rejections are classified the same way as [`Api::broadcast`](crate::Api::broadcast).
+	pub async fn broadcast<S>(&self, tx: S) -> Result<Txid>
+	where
+		S: AsRef<str>,
+	{
+		let tx = tx.as_ref();
+		let txid = txid_of(tx)?;
+
+		self.throttle().await;
+
+		let r = self.client.post(self.uri_of("tx")).body(tx.to_owned()).send().await?;
+
+		settle_broadcast(txid, Self::text_of(r).await.map(|_| ()))
+	}
+
+	fn uri_of(&self, path: &str) -> String {
+		format!("{}/{path}", self.uri.trim_end_matches('/'))
+	}
+
+	async fn throttle(&self) {
+		if let Some(l) = &self.rate_limiter {
+			l.acquire().await;
+		}
+	}
+
+	async fn get<R>(&self, path: &str) -> Result<R>
+	where
+		R: DeserializeOwned,
+	{
+		self.throttle().await;
+
+		let body = Self::text_of(self.client.get(self.uri_of(path)).send().await?).await?;
+
+		serde_json::from_str(&body).map_err(|source| Error::Decode { source, body })
+	}
+
+	// Bitcoin Core errors come through as `sendrawtransaction RPC error: {"code":..,"message":..}`.
+	async fn text_of(r: Response) -> Result<String> {
+		let status = r.status();
+		let body = r.text().await?;
+
+		if status.is_success() {
+			return Ok(body);
+		}
+
+		#[derive(Deserialize)]
+		struct RpcError {
+			code: i64,
+			message: String,
+		}
+
+		match body.split_once("RPC error: ").and_then(|(_, e)| serde_json::from_str(e).ok()) {
+			Some(RpcError { code, message }) => Err(Error::Server { code, message }),
+			None => Err(Error::Status { status: status.as_u16(), body }),
+		}
+	}
+}
+impl Chain for Esplora {
+	async fn utxos<S>(&self, address: S) -> Result<Vec<Utxo>>
+	where
+		S: Send + Sync + AsRef<str>,
+	{
+		self.address_utxos(address).await
+	}
+
+	async fn wait_for_utxo<S>(&self, address: S, satoshis: u64) -> Result<Utxo>
+	where
+		S: Send + Sync + AsRef<str>,
+	{
+		chain::poll_utxo(self, address.as_ref(), satoshis, self.wait_timeout, |us| async { Ok(us) })
+			.await
+	}
+
+	// Take the estimate of the closest target not after the given one.
+	async fn fee_rate(&self, target: u16) -> Result<u64> {
+		let es = self.fee_estimates().await?;
+		let r = es
+			.iter()
+			.filter(|(t, _)| **t <= target)
+			.max_by_key(|(t, _)| **t)
+			.or_else(|| es.iter().min_by_key(|(t, _)| **t))
+			.map(|(_, r)| *r)
+			.ok_or_else(|| Error::Server {
+				code: 0,
+				message: "no fee estimate available".into(),
+			})?;
+
+		Ok(r.ceil() as u64)
+	}
+
+	async fn send_transaction<S>(&self, tx: S) -> Result<Txid>
+	where
+		S: Send + Sync + AsRef<str>,
+	{
+		self.broadcast(tx).await
+	}
+}
+
+/// Builder for [`Esplora`].
+#[derive(Debug)]
+pub struct EsploraBuilder {
+	/// Request timeout.
+	pub timeout: Duration,
+	/// Base URI.
+	pub uri: String,
+	/// Maximum time to wait for a UTXO.
+	pub wait_timeout: Option<Duration>,
+	/// Settings of the HTTP client.
+	pub http: HttpOptions,
+}
+impl EsploraBuilder {
+	/// Set request timeout.
+	pub fn timeout(mut self, timeout: Duration) -> Self {
+		self.timeout = timeout;
+
+		self
+	}
+
+	/// Set base URI.
+	pub fn uri<S>(mut self, uri: S) -> Self
+	where
+		S: Into<String>,
+	{
+		self.uri = uri.into();
+
+		self
+	}
+
+	/// Set maximum time to wait for a UTXO.
+	pub fn wait_timeout(mut self, wait_timeout: Option<Duration>) -> Self {
+		self.wait_timeout = wait_timeout;
+
+		self
+	}
+
+	/// Set the settings of the HTTP client.
+	pub fn http(mut self, http: HttpOptions) -> Self {
+		self.http = http;
+
+		self
+	}
+
+	/// Build the [`Esplora`] client.
+	pub fn build(self) -> Result<Esplora> {
+		Ok(Esplora {
+			client: self.http.apply(ReqwestClientBuilder::new().timeout(self.timeout))?.build()?,
+			uri: self.uri,
+			wait_timeout: self.wait_timeout,
+			rate_limiter: self.http.rate_limiter(),
+		})
+	}
+}
+impl Default for EsploraBuilder {
+	fn default() -> Self {
+		Self {
+			timeout: Duration::from_secs(30),
+			uri: "https://mempool.space/api".into(),
+			wait_timeout: None,
+			http: HttpOptions::default(),
+		}
+	}
+}
+
+/// Confirmation status of a transaction.
+#[derive(Debug, Deserialize)]
+pub struct TxStatus {
+	/// Whether the transaction is in a block.
+	pub confirmed: bool,
+	/// Height of the block.
+	pub block_height: Option<u64>,
+	/// Hash of the block.
+	pub block_hash: Option<String>,
+	/// Timestamp of the block.
+	pub block_time: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Unspent {
+	txid: String,
+	vout: u32,
+	value: u64,
+}
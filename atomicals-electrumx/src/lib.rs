@@ -4,8 +4,28 @@
 
 #[cfg(test)] mod test;
 
+pub mod bitcoind;
+
+#[cfg(any(test, feature = "blocking"))] pub mod blocking;
+
+pub mod cache;
+
+pub mod chain;
+
+pub mod electrum;
+use electrum::{Electrum, ElectrumBuilder, Subscription};
+
 pub mod error;
 
+pub mod esplora;
+
+#[cfg(any(test, feature = "mock"))] pub mod mock;
+
+pub mod pool;
+
+pub mod transport;
+use transport::{HttpOptions, RateLimiter};
+
 pub mod r#type;
 use r#type::*;
 
@@ -16,7 +36,7 @@ pub mod prelude {
 
 	pub use std::result::Result as StdResult;
 
-	pub use super::error::{self, Error};
+	pub use super::error::{self, Error, Rejection};
 
 	/// atomicals-electrumx `Result` type.
 	pub type Result<T> = StdResult<T, Error>;
@@ -24,12 +44,22 @@ pub mod prelude {
 use prelude::*;
 
 // std
-use std::{future::Future, str::FromStr, time::Duration};
+use std::{
+	collections::{hash_map::Entry, HashMap},
+	future::Future,
+	str::FromStr,
+	time::Duration,
+};
 // crates.io
-use bitcoin::{Address, Amount, Network};
-use reqwest::{Client as ReqwestClient, ClientBuilder as ReqwestClientBuilder};
+use bitcoin::{
+	block::Header, consensus::encode, hex::FromHex, Address, Amount, Network, Transaction, Txid,
+};
+use futures_util::future;
+use reqwest::{
+	header::RETRY_AFTER, Client as ReqwestClient, ClientBuilder as ReqwestClientBuilder, StatusCode,
+};
 use serde::{de::DeserializeOwned, Serialize};
-use tokio::time;
+use tokio::time::{self, Instant};
 
 /// Necessary configurations of the client to transform it into an API client.
 pub trait Config {
@@ -37,6 +67,8 @@ pub trait Config {
 	fn network(&self) -> &Network;
 	/// Base URI.
 	fn base_uri(&self) -> &str;
+	/// Maximum time to wait for a UTXO, `None` to wait forever.
+	fn wait_timeout(&self) -> Option<Duration>;
 }
 
 /// Necessary HTTP methods of the client to transform it into an API client.
@@ -47,6 +79,41 @@ pub trait Http {
 		U: Send + Sync + AsRef<str>,
 		P: Send + Sync + Serialize,
 		R: DeserializeOwned;
+
+	/// Send the POST requests in one round trip if the transport supports it, concurrently
+	/// otherwise.
+	///
+	/// The results are returned in the order of the requests. The outer error means the whole
+	/// batch failed.
+	fn post_batch<U, P, R>(
+		&self,
+		requests: Vec<(U, P)>,
+	) -> impl Future<Output = Result<Vec<Result<R>>>> + Send
+	where
+		Self: Sync,
+		U: Send + Sync + AsRef<str>,
+		P: Send + Sync + Serialize,
+		R: Send + DeserializeOwned,
+	{
+		async move {
+			Ok(future::join_all(requests.iter().map(|(u, p)| self.post::<_, _, R>(u, p))).await)
+		}
+	}
+
+	/// Subscribe to the status of the scripthash and the chain headers.
+	///
+	/// Return `None` if the transport cannot push notifications.
+	fn subscribe<S>(
+		&self,
+		scripthash: S,
+	) -> impl Future<Output = Result<Option<Subscription>>> + Send
+	where
+		S: Send + Sync + AsRef<str>,
+	{
+		let _ = scripthash;
+
+		async { Ok(None) }
+	}
 }
 
 /// Atomicals electrumx APIs.
@@ -95,6 +162,262 @@ pub trait Api: Send + Sync + Config + Http {
 		}
 	}
 
+	/// Make a request at `blockchain.atomicals.get`.
+	fn get_atomical<S>(
+		&self,
+		atomical_id: S,
+	) -> impl Future<Output = Result<ResponseResult<Atomical>>> + Send
+	where
+		S: Send + Sync + AsRef<str>,
+	{
+		async move {
+			Ok(self
+				.post::<_, _, Response<ResponseResult<Atomical>>>(
+					self.uri_of("blockchain.atomicals.get"),
+					[atomical_id.as_ref()],
+				)
+				.await?
+				.response)
+		}
+	}
+
+	/// Make a request at `blockchain.atomicals.get_by_realm`.
+	fn get_by_realm<S>(&self, realm: S) -> impl Future<Output = Result<Realm>> + Send
+	where
+		S: Send + Sync + AsRef<str>,
+	{
+		async move {
+			Ok(self
+				.post::<_, _, Response<ResponseResult<Realm>>>(
+					self.uri_of("blockchain.atomicals.get_by_realm"),
+					[realm.as_ref()],
+				)
+				.await?
+				.response
+				.result)
+		}
+	}
+
+	/// Make a request at `blockchain.atomicals.get_by_container`.
+	fn get_by_container<S>(&self, container: S) -> impl Future<Output = Result<Container>> + Send
+	where
+		S: Send + Sync + AsRef<str>,
+	{
+		async move {
+			Ok(self
+				.post::<_, _, Response<ResponseResult<Container>>>(
+					self.uri_of("blockchain.atomicals.get_by_container"),
+					[container.as_ref()],
+				)
+				.await?
+				.response
+				.result)
+		}
+	}
+
+	/// Make a request at `blockchain.atomicals.get_container_item_validation`.
+	#[allow(clippy::too_many_arguments)]
+	fn get_container_item_validation<S, P>(
+		&self,
+		container: S,
+		item: S,
+		bitworkc: S,
+		bitworkr: S,
+		main: S,
+		main_hash: S,
+		proof: P,
+	) -> impl Future<Output = Result<ItemValidation>> + Send
+	where
+		S: Send + Sync + AsRef<str>,
+		P: Send + Sync + Serialize,
+	{
+		async move {
+			Ok(self
+				.post::<_, _, Response<ResponseResult<ItemValidation>>>(
+					self.uri_of("blockchain.atomicals.get_container_item_validation"),
+					(
+						container.as_ref(),
+						item.as_ref(),
+						bitworkc.as_ref(),
+						bitworkr.as_ref(),
+						main.as_ref(),
+						main_hash.as_ref(),
+						proof,
+						// Check without waiting for the container to be sealed.
+						true,
+					),
+				)
+				.await?
+				.response
+				.result)
+		}
+	}
+
+	/// Make a request at `blockchain.atomicals.get_state`.
+	fn get_state<S>(
+		&self,
+		atomical_id: S,
+	) -> impl Future<Output = Result<ResponseResult<AtomicalState>>> + Send
+	where
+		S: Send + Sync + AsRef<str>,
+	{
+		async move {
+			Ok(self
+				.post::<_, _, Response<ResponseResult<AtomicalState>>>(
+					self.uri_of("blockchain.atomicals.get_state"),
+					[atomical_id.as_ref()],
+				)
+				.await?
+				.response)
+		}
+	}
+
+	/// Make a request at `blockchain.atomicals.get_global`.
+	fn get_global(&self) -> impl Future<Output = Result<Global>> + Send {
+		async move {
+			Ok(self
+				.post::<_, _, Response<GlobalInfo>>(
+					self.uri_of("blockchain.atomicals.get_global"),
+					[(); 0],
+				)
+				.await?
+				.response
+				.global)
+		}
+	}
+
+	/// Make a request at `blockchain.atomicals.get_realm_info`.
+	fn get_realm_info<S>(
+		&self,
+		full_name: S,
+	) -> impl Future<Output = Result<ResponseResult<RealmInfo>>> + Send
+	where
+		S: Send + Sync + AsRef<str>,
+	{
+		async move {
+			Ok(self
+				.post::<_, _, Response<ResponseResult<RealmInfo>>>(
+					self.uri_of("blockchain.atomicals.get_realm_info"),
+					[full_name.as_ref()],
+				)
+				.await?
+				.response)
+		}
+	}
+
+	/// Make a request at `blockchain.atomicals.get_location`.
+	fn get_location<S>(
+		&self,
+		atomical_id: S,
+	) -> impl Future<Output = Result<ResponseResult<AtomicalLocation>>> + Send
+	where
+		S: Send + Sync + AsRef<str>,
+	{
+		async move {
+			Ok(self
+				.post::<_, _, Response<ResponseResult<AtomicalLocation>>>(
+					self.uri_of("blockchain.atomicals.get_location"),
+					[atomical_id.as_ref()],
+				)
+				.await?
+				.response)
+		}
+	}
+
+	/// Make a request at `blockchain.atomicals.get_container_items`.
+	fn get_container_items<S>(
+		&self,
+		container: S,
+		limit: u32,
+		offset: u32,
+	) -> impl Future<Output = Result<ResponseResult<ContainerItems>>> + Send
+	where
+		S: Send + Sync + AsRef<str>,
+	{
+		async move {
+			Ok(self
+				.post::<_, _, Response<ResponseResult<ContainerItems>>>(
+					self.uri_of("blockchain.atomicals.get_container_items"),
+					(container.as_ref(), limit, offset),
+				)
+				.await?
+				.response)
+		}
+	}
+
+	/// Make a request at `blockchain.atomicals.listscripthash`.
+	fn list_scripthash<S>(
+		&self,
+		scripthash: S,
+	) -> impl Future<Output = Result<ScripthashAtomicals>> + Send
+	where
+		S: Send + Sync + AsRef<str>,
+	{
+		async move {
+			Ok(self
+				.post::<_, _, Response<ScripthashAtomicals>>(
+					self.uri_of("blockchain.atomicals.listscripthash"),
+					(scripthash.as_ref(), true),
+				)
+				.await?
+				.response)
+		}
+	}
+
+	/// Make a request at `blockchain.atomicals.get_holders`.
+	fn get_holders<S>(
+		&self,
+		atomical_id: S,
+		limit: u32,
+		offset: u32,
+	) -> impl Future<Output = Result<ResponseResult<Holders>>> + Send
+	where
+		S: Send + Sync + AsRef<str>,
+	{
+		async move {
+			Ok(self
+				.post::<_, _, Response<ResponseResult<Holders>>>(
+					self.uri_of("blockchain.atomicals.get_holders"),
+					(atomical_id.as_ref(), limit, offset),
+				)
+				.await?
+				.response)
+		}
+	}
+
+	/// Make a request at `blockchain.atomicals.find_tickers`.
+	///
+	/// List the tickers starting with `prefix`, all of them if it is empty.
+	fn find_tickers<S>(
+		&self,
+		prefix: S,
+		verified_only: bool,
+		limit: u32,
+		offset: u32,
+	) -> impl Future<Output = Result<ResponseResult<Vec<TickerEntry>>>> + Send
+	where
+		S: Send + Sync + AsRef<str>,
+	{
+		async move {
+			let prefix = prefix.as_ref();
+
+			Ok(self
+				.post::<_, _, Response<ResponseResult<Vec<TickerEntry>>>>(
+					self.uri_of("blockchain.atomicals.find_tickers"),
+					(
+						(!prefix.is_empty()).then_some(prefix),
+						// Not reversed.
+						false,
+						limit,
+						offset,
+						verified_only,
+					),
+				)
+				.await?
+				.response)
+		}
+	}
+
 	/// Make a request at `blockchain.atomicals.get_by_id`.
 	fn get_unspent_address<S>(&self, address: S) -> impl Future<Output = Result<Vec<Utxo>>> + Send
 	where
@@ -108,6 +431,59 @@ pub trait Api: Send + Sync + Config + Http {
 		}
 	}
 
+	/// Make a request at `blockchain.scripthash.listunspent` for each address in one batch.
+	///
+	/// The UTXOs are returned in the order of the addresses.
+	fn get_unspent_addresses<S>(
+		&self,
+		addresses: Vec<S>,
+	) -> impl Future<Output = Result<Vec<Vec<Utxo>>>> + Send
+	where
+		S: Send + Sync + AsRef<str>,
+	{
+		async move {
+			let shs = addresses
+				.iter()
+				.map(|a| {
+					util::address2scripthash(
+						&Address::from_str(a.as_ref())?.require_network(*self.network())?,
+					)
+				})
+				.collect::<Result<Vec<_>>>()?;
+
+			self.get_unspent_scripthashes(shs).await
+		}
+	}
+
+	/// Make a request at `blockchain.scripthash.listunspent` for each scripthash in one batch.
+	///
+	/// The UTXOs are returned in the order of the scripthashes.
+	fn get_unspent_scripthashes<S>(
+		&self,
+		scripthashes: Vec<S>,
+	) -> impl Future<Output = Result<Vec<Vec<Utxo>>>> + Send
+	where
+		S: Send + Sync + AsRef<str>,
+	{
+		async move {
+			let u = self.uri_of("blockchain.scripthash.listunspent");
+
+			self.post_batch::<_, _, Response<Vec<Unspent>>>(
+				scripthashes.iter().map(|sh| (&u, [sh.as_ref()])).collect(),
+			)
+			.await?
+			.into_iter()
+			.map(|r| {
+				let mut utxos = r?.response.into_iter().map(|u| u.into()).collect::<Vec<Utxo>>();
+
+				utxos.sort_by_key(|u| u.value);
+
+				Ok(utxos)
+			})
+			.collect()
+		}
+	}
+
 	/// Make a request at `blockchain.scripthash.listunspent`.
 	fn get_unspent_scripthash<S>(
 		&self,
@@ -128,13 +504,40 @@ pub trait Api: Send + Sync + Config + Http {
 				.map(|u| u.into())
 				.collect::<Vec<Utxo>>();
 
-			utxos.sort_by(|a, b| a.value.cmp(&b.value));
+			utxos.sort_by_key(|u| u.value);
 
 			Ok(utxos)
 		}
 	}
 
+	/// Classify the UTXOs, looking up the type of each atomical they carry once.
+	fn classify_utxos(
+		&self,
+		utxos: Vec<Utxo>,
+	) -> impl Future<Output = Result<Vec<(Utxo, UtxoKind)>>> + Send {
+		async move {
+			let mut types = HashMap::new();
+
+			for a in utxos.iter().flat_map(|u| &u.atomicals) {
+				if let Entry::Vacant(e) = types.entry(a.id) {
+					e.insert(self.get_atomical(a.id.to_string()).await?.result.r#type);
+				}
+			}
+
+			Ok(utxos
+				.into_iter()
+				.map(|u| {
+					let k = u.kind(&types).expect("every type is looked up");
+
+					(u, k)
+				})
+				.collect())
+		}
+	}
+
 	/// Wait until a matching UTXO is found.
+	///
+	/// Wakes up on notifications if the transport can subscribe, otherwise polls.
 	fn wait_until_utxo<S>(
 		&self,
 		address: S,
@@ -146,10 +549,24 @@ pub trait Api: Send + Sync + Config + Http {
 		async move {
 			let a = address.as_ref();
 			let ba = Amount::from_sat(satoshis);
+			let sh = util::address2scripthash(
+				&Address::from_str(a).unwrap().require_network(*self.network())?,
+			)?;
+			let deadline = self.wait_timeout().map(|t| Instant::now() + t);
+			let mut subscription = None;
 
 			loop {
+				// Subscribe before checking so that a change in between is not missed.
+				if subscription.is_none() {
+					subscription = self.subscribe(&sh).await.unwrap_or_else(|e| {
+						tracing::warn!("failed to subscribe to {a} due to {e}, polling instead");
+
+						None
+					});
+				}
+
 				if let Some(u) = self
-					.get_unspent_address(a)
+					.get_unspent_scripthash(&sh)
 					.await?
 					.into_iter()
 					.find(|u| u.atomicals.is_empty() && u.value >= satoshis)
@@ -163,32 +580,127 @@ pub trait Api: Send + Sync + Config + Http {
 					return Ok(u);
 				}
 
+				// Still re-check once in a while in case a notification is lost.
+				let mut period = if subscription.is_some() {
+					Duration::from_secs(60)
+				} else {
+					Duration::from_secs(5)
+				};
+
+				if let Some(d) = deadline {
+					let left = d.saturating_duration_since(Instant::now());
+
+					if left.is_zero() {
+						Err(Error::WaitTimeout { address: a.into(), satoshis })?;
+					}
+
+					period = period.min(left);
+				}
+
 				tracing::info!(
 					"awaiting UTXO confirmation until {ba} BTC is received at address {a}"
 				);
 
-				time::sleep(Duration::from_secs(5)).await;
+				match subscription.as_mut() {
+					Some(s) =>
+						if let Err(e) = s.changed(period).await {
+							tracing::warn!("lost the subscription to {a} due to {e}");
+
+							subscription = None;
+						},
+					None => time::sleep(period).await,
+				}
 			}
 		}
 	}
 
-	// TODO: Return type.
-	/// Make a request at `blockchain.scripthash.get_balance`.
-	fn broadcast<S>(&self, tx: S) -> impl Future<Output = Result<serde_json::Value>> + Send
+	/// Make a request at `blockchain.transaction.get`.
+	fn get_transaction<S>(&self, txid: S) -> impl Future<Output = Result<String>> + Send
 	where
 		S: Send + Sync + AsRef<str>,
 	{
 		async move {
-			self.post::<_, _, serde_json::Value>(
-				self.uri_of("blockchain.transaction.broadcast"),
-				[tx.as_ref()],
-			)
-			.await
+			Ok(self
+				.post::<_, _, Response<String>>(
+					self.uri_of("blockchain.transaction.get"),
+					[txid.as_ref()],
+				)
+				.await?
+				.response)
+		}
+	}
+
+	/// Make a request at `blockchain.estimatefee`.
+	///
+	/// Return the fee rate in BTC/kvB to confirm within `target` blocks, or `-1` if the daemon
+	/// has no estimate.
+	fn estimate_fee(&self, target: u16) -> impl Future<Output = Result<f64>> + Send {
+		async move {
+			Ok(self
+				.post::<_, _, Response<f64>>(self.uri_of("blockchain.estimatefee"), [target])
+				.await?
+				.response)
+		}
+	}
+
+	/// Make a request at `blockchain.block.header`.
+	fn get_block_header(&self, height: u64) -> impl Future<Output = Result<Header>> + Send {
+		async move {
+			let h = self
+				.post::<_, _, Response<String>>(self.uri_of("blockchain.block.header"), [height])
+				.await?
+				.response;
+
+			Ok(encode::deserialize(&Vec::from_hex(&h)?)?)
+		}
+	}
+
+	/// Make a request at `blockchain.transaction.broadcast`.
+	///
+	/// A transaction already in the mempool or the chain counts as broadcast. Known mempool
+	/// rejections are returned as [`Error::Rejected`].
+	fn broadcast<S>(&self, tx: S) -> impl Future<Output = Result<Txid>> + Send
+	where
+		S: Send + Sync + AsRef<str>,
+	{
+		async move {
+			let tx = tx.as_ref();
+			let txid = txid_of(tx)?;
+			let r = self
+				.post::<_, _, Response<String>>(
+					self.uri_of("blockchain.transaction.broadcast"),
+					[tx],
+				)
+				.await;
+
+			settle_broadcast(txid, r.map(|_| ()))
 		}
 	}
 }
 impl<T> Api for T where T: Send + Sync + Config + Http {}
 
+fn txid_of(tx: &str) -> Result<Txid> {
+	Ok(encode::deserialize::<Transaction>(&Vec::from_hex(tx)?)?.txid())
+}
+
+// A transaction already in the mempool or the chain counts as broadcast.
+fn settle_broadcast(txid: Txid, r: Result<()>) -> Result<Txid> {
+	match r {
+		Ok(_) => Ok(txid),
+		Err(Error::Server { code, message }) =>
+			if Rejection::is_already_known(&message) {
+				tracing::info!("transaction {txid} is already known");
+
+				Ok(txid)
+			} else if let Some(reason) = Rejection::classify(&message) {
+				Err(Error::Rejected { reason, message })
+			} else {
+				Err(Error::Server { code, message })
+			},
+		Err(e) => Err(e),
+	}
+}
+
 /// Atomicals electrumx client.
 #[derive(Debug)]
 pub struct ElectrumX {
@@ -202,6 +714,10 @@ pub struct ElectrumX {
 	pub network: Network,
 	/// Base URI.
 	pub base_uri: String,
+	/// Maximum time to wait for a UTXO.
+	pub wait_timeout: Option<Duration>,
+	/// Client side rate limiter.
+	pub rate_limiter: Option<RateLimiter>,
 }
 impl Config for ElectrumX {
 	fn network(&self) -> &Network {
@@ -211,6 +727,10 @@ impl Config for ElectrumX {
 	fn base_uri(&self) -> &str {
 		&self.base_uri
 	}
+
+	fn wait_timeout(&self) -> Option<Duration> {
+		self.wait_timeout
+	}
 }
 impl Http for ElectrumX {
 	async fn post<U, P, R>(&self, uri: U, params: P) -> Result<R>
@@ -220,24 +740,41 @@ impl Http for ElectrumX {
 		R: DeserializeOwned,
 	{
 		let u = uri.as_ref();
+		let params = &params;
 
-		for _ in self.max_retries.clone() {
-			match self.client.post(u).json(&params).send().await {
-				Ok(r) => match r.json().await {
-					Ok(r) => return Ok(r),
-					Err(e) => {
-						tracing::error!("failed to parse response into JSON due to {e}");
-					},
-				},
-				Err(e) => {
-					tracing::error!("the request to {u} failed due to {e}");
-				},
+		retry(&self.max_retries, self.retry_period, u, || async move {
+			if let Some(l) = &self.rate_limiter {
+				l.acquire().await;
 			}
 
-			time::sleep(self.retry_period).await;
-		}
+			let r = self.client.post(u).json(params).send().await?;
+			let status = r.status();
+
+			if status == StatusCode::TOO_MANY_REQUESTS {
+				// Only the delay in seconds form is supported, not the HTTP date one.
+				let retry_after = r
+					.headers()
+					.get(RETRY_AFTER)
+					.and_then(|v| v.to_str().ok()?.parse().ok())
+					.map(Duration::from_secs);
 
-		Err(Error::ExceededMaximumRetries)
+				Err(Error::RateLimited { retry_after })?;
+			}
+
+			let body = r.text().await?;
+
+			if let Ok(Failure { success: false, code, message }) = serde_json::from_str(&body) {
+				Err(Error::Server {
+					code: code.unwrap_or_default(),
+					message: message.unwrap_or(body),
+				})
+			} else if !status.is_success() {
+				Err(Error::Status { status: status.as_u16(), body })
+			} else {
+				serde_json::from_str(&body).map_err(|source| Error::Decode { source, body })
+			}
+		})
+		.await
 	}
 }
 
@@ -254,6 +791,10 @@ pub struct ElectrumXBuilder {
 	pub network: Network,
 	/// Base URI.
 	pub base_uri: String,
+	/// Maximum time to wait for a UTXO.
+	pub wait_timeout: Option<Duration>,
+	/// Settings of the HTTP client.
+	pub http: HttpOptions,
 }
 impl ElectrumXBuilder {
 	#[cfg(test)]
@@ -299,14 +840,30 @@ impl ElectrumXBuilder {
 		self
 	}
 
+	/// Set maximum time to wait for a UTXO.
+	pub fn wait_timeout(mut self, wait_timeout: Option<Duration>) -> Self {
+		self.wait_timeout = wait_timeout;
+
+		self
+	}
+
+	/// Set the settings of the HTTP client.
+	pub fn http(mut self, http: HttpOptions) -> Self {
+		self.http = http;
+
+		self
+	}
+
 	/// Build the [`ElectrumX`] client.
 	pub fn build(self) -> Result<ElectrumX> {
 		Ok(ElectrumX {
-			client: ReqwestClientBuilder::new().timeout(self.timeout).build()?,
+			client: self.http.apply(ReqwestClientBuilder::new().timeout(self.timeout))?.build()?,
 			retry_period: self.retry_period,
 			max_retries: self.max_retries,
 			network: self.network,
 			base_uri: self.base_uri,
+			wait_timeout: self.wait_timeout,
+			rate_limiter: self.http.rate_limiter(),
 		})
 	}
 }
@@ -318,9 +875,180 @@ impl Default for ElectrumXBuilder {
 			max_retries: MaxRetries::Finite(5),
 			network: Network::Bitcoin,
 			base_uri: "https://ep.atomicals.xyz/proxy".into(),
+			wait_timeout: None,
+			http: HttpOptions::default(),
+		}
+	}
+}
+/// Atomicals electrumx client over either transport.
+#[derive(Debug)]
+pub enum Client {
+	/// Through the HTTP proxy.
+	Proxy(ElectrumX),
+	/// Over the electrum protocol.
+	Electrum(Electrum),
+}
+impl Client {
+	/// Build a client with default settings, picking the transport by the URI's scheme.
+	pub fn new<S>(network: Network, uri: S) -> Result<Self>
+	where
+		S: Into<String>,
+	{
+		Self::new_with_http(network, uri, &HttpOptions::default())
+	}
+
+	/// Like [`Client::new`], with the given transport settings.
+	pub fn new_with_http<S>(network: Network, uri: S, http: &HttpOptions) -> Result<Self>
+	where
+		S: Into<String>,
+	{
+		let uri = uri.into();
+
+		Ok(if electrum::is_electrum_uri(&uri) {
+			Self::Electrum(
+				ElectrumBuilder::default()
+					.network(network)
+					.base_uri(uri)
+					.http(http.to_owned())
+					.build()?,
+			)
+		} else {
+			Self::Proxy(
+				ElectrumXBuilder::default()
+					.network(network)
+					.base_uri(uri)
+					.http(http.to_owned())
+					.build()?,
+			)
+		})
+	}
+
+	/// Set maximum number of retry attempts.
+	pub fn with_max_retries(mut self, max_retries: MaxRetries) -> Self {
+		match &mut self {
+			Self::Proxy(c) => c.max_retries = max_retries,
+			Self::Electrum(c) => c.max_retries = max_retries,
+		}
+
+		self
+	}
+
+	/// Set maximum time to wait for a UTXO.
+	pub fn with_wait_timeout(mut self, wait_timeout: Option<Duration>) -> Self {
+		match &mut self {
+			Self::Proxy(c) => c.wait_timeout = wait_timeout,
+			Self::Electrum(c) => c.wait_timeout = wait_timeout,
+		}
+
+		self
+	}
+}
+impl Config for Client {
+	fn network(&self) -> &Network {
+		match self {
+			Self::Proxy(c) => c.network(),
+			Self::Electrum(c) => c.network(),
+		}
+	}
+
+	fn base_uri(&self) -> &str {
+		match self {
+			Self::Proxy(c) => c.base_uri(),
+			Self::Electrum(c) => c.base_uri(),
+		}
+	}
+
+	fn wait_timeout(&self) -> Option<Duration> {
+		match self {
+			Self::Proxy(c) => c.wait_timeout(),
+			Self::Electrum(c) => c.wait_timeout(),
+		}
+	}
+}
+impl Http for Client {
+	async fn post<U, P, R>(&self, uri: U, params: P) -> Result<R>
+	where
+		U: Send + Sync + AsRef<str>,
+		P: Send + Sync + Serialize,
+		R: DeserializeOwned,
+	{
+		match self {
+			Self::Proxy(c) => c.post(uri, params).await,
+			Self::Electrum(c) => c.post(uri, params).await,
+		}
+	}
+
+	async fn post_batch<U, P, R>(&self, requests: Vec<(U, P)>) -> Result<Vec<Result<R>>>
+	where
+		U: Send + Sync + AsRef<str>,
+		P: Send + Sync + Serialize,
+		R: Send + DeserializeOwned,
+	{
+		match self {
+			Self::Proxy(c) => c.post_batch(requests).await,
+			Self::Electrum(c) => c.post_batch(requests).await,
 		}
 	}
+
+	async fn subscribe<S>(&self, scripthash: S) -> Result<Option<Subscription>>
+	where
+		S: Send + Sync + AsRef<str>,
+	{
+		match self {
+			Self::Proxy(c) => c.subscribe(scripthash).await,
+			Self::Electrum(c) => c.subscribe(scripthash).await,
+		}
+	}
+}
+
+// Send the request until it succeeds, fails for good or runs out of retries.
+async fn retry<F, Fut, T>(
+	max_retries: &MaxRetries,
+	retry_period: Duration,
+	target: &str,
+	mut request: F,
+) -> Result<T>
+where
+	F: FnMut() -> Fut,
+	Fut: Future<Output = Result<T>>,
+{
+	let mut retries = max_retries.clone();
+	let mut rate_limited = 0;
+
+	loop {
+		let delay = match request().await {
+			Ok(r) => return Ok(r),
+			Err(e) if !e.is_retryable() => Err(e)?,
+			Err(e) => {
+				tracing::error!("the request to {target} failed due to {e}");
+
+				let delay = retry_delay(&e, retry_period, &mut rate_limited);
+
+				if retries.next().is_none() {
+					Err(Error::ExceededMaximumRetries(Box::new(e)))?;
+				}
+
+				delay
+			},
+		};
+
+		time::sleep(delay).await;
+	}
 }
+
+// Back off harder on each rate limit, wait for the retry period otherwise.
+fn retry_delay(e: &Error, retry_period: Duration, rate_limited: &mut u32) -> Duration {
+	match e {
+		Error::RateLimited { retry_after } => {
+			*rate_limited += 1;
+
+			transport::backoff(retry_period, *rate_limited - 1, *retry_after)
+		},
+		Error::ExceededMaximumRetries(e) => retry_delay(e, retry_period, rate_limited),
+		_ => retry_period,
+	}
+}
+
 /// Maximum number of retry attempts.
 #[derive(Debug, Clone)]
 pub enum MaxRetries {
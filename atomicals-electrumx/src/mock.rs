@@ -0,0 +1,410 @@
+//! In-process mock of the electrumx HTTP proxy, for tests.
+//!
+//! The state is shared with the server and can be scripted at any time. Accepted broadcasts are
+//! applied to the UTXO set, so a transaction's outputs can be waited for right after it.
+
+// std
+use std::{
+	collections::{HashMap, VecDeque},
+	sync::{Arc, Mutex, MutexGuard},
+};
+// crates.io
+use bitcoin::{
+	consensus::encode, hashes::Hash, hex::FromHex, Address, OutPoint, Transaction, Txid,
+};
+use serde_json::{json, Value};
+use tokio::{
+	io::{AsyncReadExt, AsyncWriteExt},
+	net::{TcpListener, TcpStream},
+};
+// atomicals-electrumx
+use crate::{
+	r#type::{AtomicalId, Utxo, UtxoAtomical},
+	util,
+};
+
+/// Mock electrumx HTTP proxy.
+#[derive(Debug)]
+pub struct Mock {
+	/// Base URI of the proxy.
+	pub uri: String,
+	state: Arc<Mutex<State>>,
+}
+impl Mock {
+	/// Start serving in the background of the current runtime.
+	pub async fn start() -> Self {
+		let l = TcpListener::bind("127.0.0.1:0").await.unwrap();
+		let uri = format!("http://{}/proxy", l.local_addr().unwrap());
+		let state = Arc::new(Mutex::new(State { height: 1, ..Default::default() }));
+		let state_ = state.clone();
+
+		tokio::spawn(async move {
+			while let Ok((s, _)) = l.accept().await {
+				tokio::spawn(serve(s, state_.clone()));
+			}
+		});
+
+		Self { uri, state }
+	}
+
+	/// Access the state.
+	pub fn state(&self) -> MutexGuard<'_, State> {
+		self.state.lock().unwrap()
+	}
+}
+
+/// State of the [`Mock`].
+#[derive(Debug, Default)]
+pub struct State {
+	/// Chain height.
+	pub height: u64,
+	/// Verified tickers.
+	pub tickers: HashMap<String, AtomicalId>,
+	/// FTs by their IDs.
+	pub fts: HashMap<AtomicalId, Ft>,
+	/// UTXOs by scripthash.
+	pub utxos: HashMap<String, Vec<Utxo>>,
+	/// Accepted transactions, in order.
+	pub broadcasts: Vec<Transaction>,
+	/// Messages to reject the next broadcasts with, in order.
+	pub rejections: VecDeque<String>,
+}
+impl State {
+	/// Deploy a verified FT, returning its ID.
+	pub fn add_ft(&mut self, ft: Ft) -> AtomicalId {
+		let id = AtomicalId { txid: Txid::hash(ft.ticker.as_bytes()), index: 0 };
+
+		self.tickers.insert(ft.ticker.clone(), id);
+		self.fts.insert(id, ft);
+
+		id
+	}
+
+	/// Send `value` to the address out of thin air.
+	pub fn fund(&mut self, address: &Address, value: u64) -> Utxo {
+		self.fund_atomicals(address, value, &[])
+	}
+
+	/// Send `value` carrying the atomicals to the address out of thin air.
+	pub fn fund_atomicals(
+		&mut self,
+		address: &Address,
+		value: u64,
+		atomicals: &[AtomicalId],
+	) -> Utxo {
+		let u = Utxo {
+			txid: Txid::hash(&rand_bytes()).to_string(),
+			vout: 0,
+			value,
+			atomicals: atomicals.iter().map(|&id| UtxoAtomical { id, value: None }).collect(),
+		};
+
+		self.utxos_mut(address).push(u.clone());
+
+		u
+	}
+
+	/// Reject the next broadcast with the message.
+	pub fn reject_next<S>(&mut self, message: S)
+	where
+		S: Into<String>,
+	{
+		self.rejections.push_back(message.into());
+	}
+
+	/// UTXOs of the address.
+	pub fn utxos_of(&self, address: &Address) -> Vec<Utxo> {
+		self.utxos
+			.get(&util::script2scripthash(&address.script_pubkey()))
+			.cloned()
+			.unwrap_or_default()
+	}
+
+	fn utxos_mut(&mut self, address: &Address) -> &mut Vec<Utxo> {
+		self.utxos.entry(util::script2scripthash(&address.script_pubkey())).or_default()
+	}
+
+	fn global(&self) -> Value {
+		json!({
+			"atomical_count": self.fts.len(),
+			"atomicals_block_hashes": {},
+			"atomicals_block_tip": "00".repeat(32),
+			"block_tip": "00".repeat(32),
+			"coin": "BitcoinTestnet",
+			"height": self.height,
+			"network": "testnet",
+			"server_time": "1970-01-01T00:00:00",
+		})
+	}
+
+	fn broadcast(&mut self, tx: &str) -> Result<Value, String> {
+		let tx = Vec::from_hex(tx)
+			.ok()
+			.and_then(|b| encode::deserialize::<Transaction>(&b).ok())
+			.ok_or("TX decode failed")?;
+
+		if let Some(m) = self.rejections.pop_front() {
+			Err(m)?;
+		}
+
+		let txid = tx.txid();
+		let spent = tx.input.iter().map(|i| i.previous_output).collect::<Vec<_>>();
+
+		for us in self.utxos.values_mut() {
+			us.retain(|u| !spent.contains(&OutPoint::new(u.txid.parse().unwrap(), u.vout)));
+		}
+		for (i, o) in tx.output.iter().enumerate() {
+			if o.script_pubkey.is_op_return() {
+				continue;
+			}
+
+			self.utxos.entry(util::script2scripthash(&o.script_pubkey)).or_default().push(Utxo {
+				txid: txid.to_string(),
+				vout: i as _,
+				value: o.value.to_sat(),
+				atomicals: Vec::new(),
+			});
+		}
+
+		self.broadcasts.push(tx);
+
+		Ok(json!(txid.to_string()))
+	}
+
+	fn handle(&mut self, method: &str, params: &Value) -> Result<Value, String> {
+		let p0 = params[0].as_str().unwrap_or_default();
+
+		Ok(match method {
+			"blockchain.atomicals.get_global" => return Ok(json!({ "global": self.global() })),
+			"blockchain.atomicals.get_by_ticker" => {
+				let id = self.tickers.get(p0);
+
+				json!({
+					"status": if id.is_some() { "verified" } else { "not_found" },
+					"candidate_atomical_id": id,
+					"atomical_id": id,
+					"candidates": [],
+					"type": "ticker",
+				})
+			},
+			"blockchain.atomicals.get" => {
+				let id = p0.parse::<AtomicalId>().map_err(|e| e.to_string())?;
+
+				self.fts.get(&id).ok_or("atomical not found")?;
+
+				json!({
+					"atomical_id": id,
+					"atomical_number": 0,
+					"confirmed": true,
+					"subtype": "decentralized",
+					"type": "FT",
+				})
+			},
+			"blockchain.atomicals.get_ft_info" => {
+				let id = p0.parse::<AtomicalId>().map_err(|e| e.to_string())?;
+
+				self.fts.get(&id).ok_or("atomical not found")?.to_json(id)
+			},
+			"blockchain.scripthash.listunspent" =>
+				return Ok(self
+					.utxos
+					.get(p0)
+					.into_iter()
+					.flatten()
+					.map(|u| {
+						json!({
+							"txid": u.txid,
+							"tx_hash": u.txid,
+							"index": u.vout,
+							"tx_pos": u.vout,
+							"vout": u.vout,
+							"height": self.height,
+							"value": u.value,
+							"atomicals": u.atomicals.iter().map(|a| a.id).collect::<Vec<_>>(),
+						})
+					})
+					.collect()),
+			"blockchain.transaction.get" => {
+				let txid = p0.parse::<Txid>().map_err(|e| e.to_string())?;
+
+				return self
+					.broadcasts
+					.iter()
+					.find(|t| t.txid() == txid)
+					.map(|t| json!(encode::serialize_hex(t)))
+					.ok_or_else(|| "No such mempool or blockchain transaction".into());
+			},
+			"blockchain.transaction.broadcast" => return self.broadcast(p0),
+			"blockchain.estimatefee" => return Ok(json!(0.00001)),
+			m => Err(format!("unknown method {m}"))?,
+		})
+		.map(|r| json!({ "global": self.global(), "result": r }))
+	}
+}
+
+/// Decentralized FT deployment.
+#[derive(Clone, Debug)]
+pub struct Ft {
+	/// Ticker.
+	pub ticker: String,
+	/// Amount of each mint.
+	pub mint_amount: u64,
+	/// Maximum number of mints.
+	pub max_mints: u64,
+	/// Number of mints so far.
+	pub mint_count: u64,
+	/// Height the minting starts at.
+	pub mint_height: u64,
+	/// Bitwork of the commit transactions.
+	pub mint_bitworkc: String,
+	/// Bitwork of the reveal transactions.
+	pub mint_bitworkr: Option<String>,
+}
+impl Ft {
+	/// Deployment easy to mint, with a one-digit commit bitwork.
+	pub fn easy<S>(ticker: S) -> Self
+	where
+		S: Into<String>,
+	{
+		Self {
+			ticker: ticker.into(),
+			mint_amount: 1_000,
+			max_mints: 21_000,
+			mint_count: 0,
+			mint_height: 0,
+			mint_bitworkc: "a".into(),
+			mint_bitworkr: None,
+		}
+	}
+
+	fn to_json(&self, id: AtomicalId) -> Value {
+		let Self {
+			ticker,
+			mint_amount,
+			max_mints,
+			mint_count,
+			mint_height,
+			mint_bitworkc,
+			mint_bitworkr,
+		} = self;
+		let bitwork = json!({ "bitworkc": "0000", "bitworkr": null });
+		let args = json!({
+			"bitworkc": "0000",
+			"max_mints": max_mints,
+			"mint_amount": mint_amount,
+			"mint_bitworkc": mint_bitworkc,
+			"mint_bitworkr": mint_bitworkr,
+			"mint_height": mint_height,
+			"request_ticker": ticker,
+			"time": 0,
+		});
+		let location = format!("{}i0", id.txid);
+
+		json!({
+			"$bitwork": bitwork,
+			"$max_mints": max_mints,
+			"$max_supply": max_mints * mint_amount,
+			"$mint_amount": mint_amount,
+			"$mint_bitworkc": mint_bitworkc,
+			"$mint_bitworkr": mint_bitworkr,
+			"$mint_height": mint_height,
+			"$request_ticker": ticker,
+			"$request_ticker_status": {
+				"note": "",
+				"status": "verified",
+				"verified_atomical_id": id,
+			},
+			"$ticker": ticker,
+			"$ticker_candidates": [],
+			"atomical_id": id,
+			"atomical_number": 0,
+			"atomical_ref": id.to_atomical_ref(),
+			"confirmed": true,
+			"dft_info": { "mint_count": mint_count },
+			"location_summary": {
+				"circulating_supply": mint_count * mint_amount,
+				"unique_holders": 0,
+			},
+			"mint_data": { "fields": { "args": args } },
+			"mint_info": {
+				"$bitwork": bitwork,
+				"$mint_bitworkc": mint_bitworkc,
+				"$mint_bitworkr": mint_bitworkr,
+				"$request_ticker": ticker,
+				"args": args,
+				"commit_height": 0,
+				"commit_index": 0,
+				"commit_location": location,
+				"commit_tx_num": 0,
+				"commit_txid": id.txid.to_string(),
+				"ctx": {},
+				"meta": {},
+				"reveal_location": location,
+				"reveal_location_blockhash": "00".repeat(32),
+				"reveal_location_header": "00".repeat(80),
+				"reveal_location_height": 0,
+				"reveal_location_index": 0,
+				"reveal_location_script": "",
+				"reveal_location_scripthash": "",
+				"reveal_location_tx_num": 0,
+				"reveal_location_txid": id.txid.to_string(),
+				"reveal_location_value": 0,
+			},
+			"subtype": "decentralized",
+			"type": "FT",
+		})
+	}
+}
+
+// Answer one request per connection.
+async fn serve(mut s: TcpStream, state: Arc<Mutex<State>>) {
+	let mut req = Vec::new();
+	let mut buf = [0; 4096];
+	let (head, body) = loop {
+		let Ok(n) = s.read(&mut buf).await else { return };
+
+		if n == 0 {
+			return;
+		}
+
+		req.extend_from_slice(&buf[..n]);
+
+		let r = String::from_utf8_lossy(&req);
+
+		if let Some(i) = r.find("\r\n\r\n") {
+			let len = r
+				.lines()
+				.find_map(|l| l.to_lowercase().strip_prefix("content-length: ")?.parse().ok())
+				.unwrap_or(0);
+
+			if req.len() >= i + 4 + len {
+				break (r[..i].to_owned(), r[i + 4..].to_owned());
+			}
+		}
+	};
+	let method = head
+		.split_whitespace()
+		.nth(1)
+		.and_then(|p| p.rsplit('/').next())
+		.unwrap_or_default()
+		.to_owned();
+	let params = serde_json::from_str::<Value>(&body).unwrap_or_default();
+	let r = match state.lock().unwrap().handle(&method, &params) {
+		Ok(r) => json!({ "success": true, "response": r }),
+		Err(message) => json!({ "success": false, "code": 1, "message": message }),
+	}
+	.to_string();
+	let _ = s
+		.write_all(
+			format!(
+				"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{r}",
+				r.len()
+			)
+			.as_bytes(),
+		)
+		.await;
+}
+
+fn rand_bytes() -> [u8; 32] {
+	bitcoin::secp256k1::rand::random()
+}
@@ -0,0 +1,676 @@
+//! Pool of electrumx endpoints.
+//!
+//! Requests go to the healthiest endpoint first and fail over to the others. Endpoints are
+//! probed for their latency and chain height, and those behind the best height are avoided.
+
+// std
+use std::{
+	sync::{
+		atomic::{AtomicU64, Ordering},
+		Mutex as StdMutex,
+	},
+	time::Duration,
+};
+// crates.io
+use bitcoin::{Network, Txid};
+use futures_util::future;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use tokio::{
+	sync::Mutex,
+	time::{self, Instant},
+};
+// atomicals-electrumx
+use crate::{
+	electrum::Subscription, prelude::*, retry_delay, transport::HttpOptions, Api, Client, Config,
+	Http, MaxRetries,
+};
+
+/// Pool of electrumx endpoints.
+#[derive(Debug)]
+pub struct Pool<C> {
+	/// Retry period, once every endpoint failed.
+	pub retry_period: Duration,
+	/// Maximum number of retry attempts, once every endpoint failed.
+	pub max_retries: MaxRetries,
+	/// Probe the endpoints at most this often.
+	pub probe_interval: Duration,
+	/// Give up on a probe after this long.
+	pub probe_timeout: Duration,
+	/// Number of blocks an endpoint may be behind the best one before being avoided.
+	///
+	/// A single endpoint is never avoided, there is nothing to fail over to.
+	pub max_lag: u64,
+	/// Maximum time to wait for a UTXO.
+	pub wait_timeout: Option<Duration>,
+	network: Network,
+	members: Vec<Member<C>>,
+	best_height: AtomicU64,
+	last_probe: Mutex<Option<Instant>>,
+}
+impl<C> Pool<C>
+where
+	C: Api,
+{
+	/// Create a pool of the clients with default settings.
+	pub fn new(clients: Vec<C>) -> Result<Self> {
+		let network = *clients.first().ok_or(Error::EmptyPool)?.network();
+
+		if clients.iter().any(|c| *c.network() != network) {
+			Err(Error::NetworkMismatch)?;
+		}
+
+		Ok(Self {
+			retry_period: Duration::from_secs(5),
+			max_retries: MaxRetries::Finite(5),
+			probe_interval: Duration::from_secs(60),
+			probe_timeout: Duration::from_secs(5),
+			// Tolerate an endpoint which hasn't seen the latest block yet.
+			max_lag: 1,
+			wait_timeout: None,
+			network,
+			members: clients.into_iter().map(Member::new).collect(),
+			best_height: AtomicU64::new(0),
+			last_probe: Mutex::new(None),
+		})
+	}
+
+	/// Probe every endpoint for its latency and chain height.
+	///
+	/// Skipped if the last probe is more recent than the probe interval, unless forced.
+	pub async fn probe(&self, force: bool) {
+		let mut last = self.last_probe.lock().await;
+
+		if !force && last.is_some_and(|t| t.elapsed() < self.probe_interval) {
+			return;
+		}
+
+		future::join_all(self.members.iter().map(|m| async move {
+			let now = Instant::now();
+
+			match time::timeout(self.probe_timeout, m.client.get_global()).await {
+				Ok(Ok(g)) => {
+					m.update(|s| {
+						s.healthy = true;
+						s.latency = Some(now.elapsed());
+						s.height = g.height;
+					});
+					self.best_height.fetch_max(g.height, Ordering::SeqCst);
+				},
+				Ok(Err(e)) => m.fail(&e),
+				Err(_) => m.fail(&Error::Timeout),
+			}
+		}))
+		.await;
+
+		*last = Some(Instant::now());
+
+		for m in &self.members {
+			let s = m.state();
+
+			tracing::debug!(
+				"endpoint {} is {} with a latency of {:?} at height {}",
+				m.client.base_uri(),
+				if s.healthy { "healthy" } else { "unhealthy" },
+				s.latency,
+				s.height
+			);
+		}
+	}
+
+	// Healthy endpoints first, then those at the best height, then the fastest ones.
+	fn ranked(&self) -> Vec<&Member<C>> {
+		let best = self.best_height.load(Ordering::SeqCst);
+		let mut ms = self.members.iter().collect::<Vec<_>>();
+
+		ms.sort_by_key(|m| {
+			let s = m.state();
+
+			(!s.healthy, s.height + self.max_lag < best, s.latency.unwrap_or(Duration::MAX))
+		});
+
+		ms
+	}
+
+	// Never let a lagging endpoint answer while another one might be up to date, its view of the
+	// chain is stale.
+	fn check_lag(&self, member: &Member<C>, response: &Value) -> Result<()> {
+		let Some(h) = response["response"]["global"]["height"].as_u64() else {
+			return Ok(());
+		};
+		let best = self.best_height.load(Ordering::SeqCst);
+
+		member.update(|s| s.height = h);
+
+		if self.members.len() > 1 && h + self.max_lag < best {
+			Err(Error::Lagging { uri: member.client.base_uri().into(), height: h, best })?;
+		}
+
+		self.best_height.fetch_max(h, Ordering::SeqCst);
+
+		Ok(())
+	}
+
+	async fn try_post(&self, method: &str, params: &Value) -> Result<Value> {
+		let ms = self.ranked();
+		let mut last_error = Error::EmptyPool;
+
+		for m in ms {
+			let u = m.client.uri_of(method);
+
+			match m.client.post::<_, _, Value>(&u, params).await {
+				Ok(r) => match self.check_lag(m, &r) {
+					Ok(()) => return Ok(r),
+					Err(e) => {
+						tracing::warn!("{e}; failing over");
+
+						last_error = e;
+					},
+				},
+				// The request reached a server which refused it, another one would too.
+				Err(e @ Error::Server { .. }) if !e.is_retryable() => Err(e)?,
+				Err(e) => {
+					tracing::warn!("endpoint {u} failed due to {e}; failing over");
+
+					m.fail(&e);
+
+					last_error = e;
+				},
+			}
+		}
+
+		Err(last_error)
+	}
+
+	/// Broadcast the transaction to every endpoint at once.
+	pub async fn broadcast_all<S>(&self, tx: S) -> Vec<Outcome>
+	where
+		S: Send + Sync + AsRef<str>,
+	{
+		let tx = tx.as_ref();
+
+		future::join_all(self.members.iter().map(|m| async move {
+			Outcome { backend: m.client.base_uri().into(), result: m.client.broadcast(tx).await }
+		}))
+		.await
+	}
+}
+
+/// Outcome of a broadcast to one backend.
+#[derive(Debug)]
+pub struct Outcome {
+	/// Backend the transaction was sent to.
+	pub backend: String,
+	/// Txid if accepted, the reason otherwise.
+	pub result: Result<Txid>,
+}
+impl Pool<Client> {
+	/// Create a pool of clients from the URIs.
+	///
+	/// The clients give up right away so that the pool can fail over.
+	pub fn from_uris<I, S>(network: Network, uris: I) -> Result<Self>
+	where
+		I: IntoIterator<Item = S>,
+		S: Into<String>,
+	{
+		Self::from_uris_with_http(network, uris, &HttpOptions::default())
+	}
+
+	/// Like [`Pool::from_uris`], with the given transport settings.
+	pub fn from_uris_with_http<I, S>(network: Network, uris: I, http: &HttpOptions) -> Result<Self>
+	where
+		I: IntoIterator<Item = S>,
+		S: Into<String>,
+	{
+		Self::new(
+			uris.into_iter()
+				.map(|u| {
+					Ok(Client::new_with_http(network, u, http)?
+						.with_max_retries(MaxRetries::Finite(0)))
+				})
+				.collect::<Result<_>>()?,
+		)
+	}
+}
+impl<C> Config for Pool<C> {
+	fn network(&self) -> &Network {
+		&self.network
+	}
+
+	// Methods are resolved against each endpoint instead.
+	fn base_uri(&self) -> &str {
+		""
+	}
+
+	fn wait_timeout(&self) -> Option<Duration> {
+		self.wait_timeout
+	}
+}
+impl<C> Http for Pool<C>
+where
+	C: Api,
+{
+	async fn post<U, P, R>(&self, uri: U, params: P) -> Result<R>
+	where
+		U: Send + Sync + AsRef<str>,
+		P: Send + Sync + Serialize,
+		R: DeserializeOwned,
+	{
+		let u = uri.as_ref();
+		let method = u.rsplit_once('/').map(|(_, m)| m).unwrap_or(u);
+		let params = serde_json::to_value(params)?;
+		let mut retries = self.max_retries.clone();
+		let mut rate_limited = 0;
+
+		loop {
+			self.probe(false).await;
+
+			let delay = match self.try_post(method, &params).await {
+				Ok(r) =>
+					return R::deserialize(&r)
+						.map_err(|source| Error::Decode { source, body: r.to_string() }),
+				Err(e @ Error::Server { .. }) if !e.is_retryable() => Err(e)?,
+				Err(e) => {
+					tracing::error!(
+						"every endpoint failed the request to {method}; last error: {e}"
+					);
+
+					let delay = retry_delay(&e, self.retry_period, &mut rate_limited);
+
+					if retries.next().is_none() {
+						Err(Error::ExceededMaximumRetries(Box::new(e)))?;
+					}
+
+					delay
+				},
+			};
+
+			time::sleep(delay).await;
+			self.probe(true).await;
+		}
+	}
+
+	// Send the batch to one endpoint, then what is left to the next ones.
+	//
+	// Only the requests which failed for a retryable reason are sent again, an answer of a
+	// lagging endpoint included.
+	async fn post_batch<U, P, R>(&self, requests: Vec<(U, P)>) -> Result<Vec<Result<R>>>
+	where
+		U: Send + Sync + AsRef<str>,
+		P: Send + Sync + Serialize,
+		R: Send + DeserializeOwned,
+	{
+		let requests = requests
+			.iter()
+			.map(|(u, p)| {
+				let u = u.as_ref();
+
+				Ok((u.rsplit_once('/').map(|(_, m)| m).unwrap_or(u), serde_json::to_value(p)?))
+			})
+			.collect::<Result<Vec<_>>>()?;
+		let mut results = requests.iter().map(|_| None).collect::<Vec<Option<Result<Value>>>>();
+		let mut errors = requests.iter().map(|_| None).collect::<Vec<Option<Error>>>();
+		let mut retries = self.max_retries.clone();
+		let mut rate_limited = 0;
+
+		loop {
+			self.probe(false).await;
+
+			// Set if an endpoint failed the whole batch.
+			let mut last_error = None;
+
+			for m in self.ranked() {
+				let left =
+					(0..requests.len()).filter(|&i| results[i].is_none()).collect::<Vec<_>>();
+
+				if left.is_empty() {
+					break;
+				}
+
+				let rs = left
+					.iter()
+					.map(|&i| (m.client.uri_of(requests[i].0), &requests[i].1))
+					.collect();
+
+				match m.client.post_batch::<_, _, Value>(rs).await {
+					Ok(rs) =>
+						for (i, r) in left.into_iter().zip(rs) {
+							match r.and_then(|r| self.check_lag(m, &r).map(|_| r)) {
+								Err(e) if e.is_retryable() => errors[i] = Some(e),
+								r => results[i] = Some(r),
+							}
+						},
+					Err(e) => {
+						tracing::warn!(
+							"endpoint {} failed due to {e}; failing over",
+							m.client.base_uri()
+						);
+
+						m.fail(&e);
+
+						last_error = Some(e);
+					},
+				}
+			}
+
+			if results.iter().all(Option::is_some) {
+				break;
+			}
+
+			let delay = {
+				let e = last_error
+					.as_ref()
+					.or_else(|| errors.iter().flatten().next())
+					.unwrap_or(&Error::EmptyPool);
+
+				tracing::error!("every endpoint failed the batch; last error: {e}");
+
+				retry_delay(e, self.retry_period, &mut rate_limited)
+			};
+
+			if retries.next().is_none() {
+				// Give up on the whole batch only if a request was never answered.
+				if results.iter().zip(&errors).any(|(r, e)| r.is_none() && e.is_none()) {
+					Err(Error::ExceededMaximumRetries(Box::new(
+						last_error.unwrap_or(Error::EmptyPool),
+					)))?;
+				}
+
+				for (r, e) in results.iter_mut().zip(&mut errors) {
+					if r.is_none() {
+						*r = e.take().map(|e| Err(Error::ExceededMaximumRetries(Box::new(e))));
+					}
+				}
+
+				break;
+			}
+
+			time::sleep(delay).await;
+			self.probe(true).await;
+		}
+
+		Ok(results
+			.into_iter()
+			.map(|r| {
+				let r = r.expect("every request is settled")?;
+
+				R::deserialize(&r).map_err(|source| Error::Decode { source, body: r.to_string() })
+			})
+			.collect())
+	}
+
+	async fn subscribe<S>(&self, scripthash: S) -> Result<Option<Subscription>>
+	where
+		S: Send + Sync + AsRef<str>,
+	{
+		let sh = scripthash.as_ref();
+
+		self.probe(false).await;
+
+		for m in self.ranked() {
+			match m.client.subscribe(sh).await {
+				Ok(Some(s)) => return Ok(Some(s)),
+				// Look for another endpoint which can push notifications.
+				Ok(None) => (),
+				Err(e) => m.fail(&e),
+			}
+		}
+
+		Ok(None)
+	}
+}
+
+#[derive(Debug)]
+struct Member<C> {
+	client: C,
+	state: StdMutex<State>,
+}
+impl<C> Member<C>
+where
+	C: Config,
+{
+	fn new(client: C) -> Self {
+		// Untried endpoints are assumed healthy until the first probe.
+		Self { client, state: StdMutex::new(State { healthy: true, latency: None, height: 0 }) }
+	}
+
+	fn state(&self) -> State {
+		self.state.lock().unwrap().clone()
+	}
+
+	fn update<F>(&self, f: F)
+	where
+		F: FnOnce(&mut State),
+	{
+		f(&mut self.state.lock().unwrap());
+	}
+
+	fn fail(&self, e: &Error) {
+		tracing::warn!("marking endpoint {} unhealthy due to {e}", self.client.base_uri());
+
+		self.update(|s| {
+			s.healthy = false;
+			s.latency = None;
+		});
+	}
+}
+
+#[derive(Clone, Debug)]
+struct State {
+	healthy: bool,
+	latency: Option<Duration>,
+	height: u64,
+}
+
+#[cfg(test)]
+#[derive(Debug)]
+struct Fake {
+	uri: String,
+	height: u64,
+	error: Option<fn() -> Error>,
+	calls: AtomicU64,
+}
+#[cfg(test)]
+impl Fake {
+	fn new(uri: &str, height: u64, error: Option<fn() -> Error>) -> Self {
+		Self { uri: uri.into(), height, error, calls: AtomicU64::new(0) }
+	}
+}
+#[cfg(test)]
+impl Config for Fake {
+	fn network(&self) -> &Network {
+		&Network::Bitcoin
+	}
+
+	fn base_uri(&self) -> &str {
+		&self.uri
+	}
+
+	fn wait_timeout(&self) -> Option<Duration> {
+		None
+	}
+}
+#[cfg(test)]
+impl Http for Fake {
+	async fn post<U, P, R>(&self, uri: U, _: P) -> Result<R>
+	where
+		U: Send + Sync + AsRef<str>,
+		P: Send + Sync + Serialize,
+		R: DeserializeOwned,
+	{
+		let global = serde_json::json!({
+			"atomical_count": 0,
+			"atomicals_block_hashes": {},
+			"atomicals_block_tip": "",
+			"block_tip": "",
+			"coin": "Bitcoin",
+			"height": self.height,
+			"network": "mainnet",
+			"server_time": ""
+		});
+
+		if uri.as_ref().ends_with("get_global") {
+			return Ok(serde_json::from_value(
+				serde_json::json!({ "success": true, "response": { "global": global } }),
+			)?);
+		}
+
+		self.calls.fetch_add(1, Ordering::SeqCst);
+
+		if let Some(e) = self.error {
+			Err(e())?;
+		}
+
+		Ok(serde_json::from_value(serde_json::json!({
+			"success": true,
+			"response": { "global": global, "result": self.uri }
+		}))?)
+	}
+}
+#[test]
+fn pool_should_work() {
+	// crates.io
+	use tokio::runtime::Runtime;
+
+	let request = |p: Pool<Fake>| async move {
+		let mut p = p;
+
+		p.retry_period = Duration::ZERO;
+		p.max_retries = MaxRetries::Finite(0);
+
+		let r = p.post::<_, _, Value>(p.uri_of("m"), ()).await;
+
+		(p, r.map(|r| r["response"]["result"].clone()))
+	};
+
+	Runtime::new().unwrap().block_on(async {
+		// Fail over to the next endpoint.
+		let (_, r) = request(
+			Pool::new(vec![
+				Fake::new("a", 10, Some(|| Error::ConnectionClosed)),
+				Fake::new("b", 10, None),
+			])
+			.unwrap(),
+		)
+		.await;
+
+		assert_eq!(r.unwrap(), "b");
+
+		// Avoid the lagging endpoint, but not one which only misses the latest block.
+		let (_, r) =
+			request(Pool::new(vec![Fake::new("a", 8, None), Fake::new("b", 10, None)]).unwrap())
+				.await;
+
+		assert_eq!(r.unwrap(), "b");
+
+		let (_, r) =
+			request(Pool::new(vec![Fake::new("a", 9, None), Fake::new("b", 10, None)]).unwrap())
+				.await;
+
+		assert!(r.is_ok());
+
+		// A single endpoint has nothing to fail over to, e.g. a load balanced proxy whose
+		// backends are at different heights.
+		let mut p = Pool::new(vec![Fake::new("a", 8, None)]).unwrap();
+
+		p.probe(true).await;
+		p.best_height.store(10, Ordering::SeqCst);
+		p.max_retries = MaxRetries::Finite(0);
+
+		assert_eq!(
+			p.post::<_, _, Value>(p.uri_of("m"), ()).await.unwrap()["response"]["result"],
+			"a"
+		);
+
+		// Refuse a lagging answer even if it's the only one.
+		let (_, r) = request(
+			Pool::new(vec![
+				Fake::new("a", 8, None),
+				Fake::new("b", 10, Some(|| Error::ConnectionClosed)),
+			])
+			.unwrap(),
+		)
+		.await;
+
+		assert!(matches!(
+			r,
+			Err(Error::ExceededMaximumRetries(e))
+				if matches!(*e, Error::Lagging { height: 8, best: 10, .. })
+		));
+
+		// Don't fail over what a server refused.
+		let (p, r) = request(
+			Pool::new(vec![
+				Fake::new("a", 10, Some(|| Error::Server { code: 1, message: "bad".into() })),
+				// Lagging, so that it's ranked last.
+				Fake::new("b", 8, None),
+			])
+			.unwrap(),
+		)
+		.await;
+
+		assert!(matches!(r, Err(Error::Server { code: 1, .. })));
+		assert_eq!(p.members[1].client.calls.load(Ordering::SeqCst), 0);
+
+		// Give up once every endpoint failed.
+		let (_, r) =
+			request(Pool::new(vec![Fake::new("a", 10, Some(|| Error::Timeout))]).unwrap()).await;
+
+		assert!(matches!(r, Err(Error::ExceededMaximumRetries(_))));
+	});
+
+	assert!(matches!(Pool::<Fake>::new(Vec::new()), Err(Error::EmptyPool)));
+}
+#[test]
+fn pool_post_batch_should_work() {
+	// crates.io
+	use tokio::runtime::Runtime;
+
+	let batch = |fakes: Vec<Fake>| async move {
+		let mut p = Pool::new(fakes).unwrap();
+
+		p.retry_period = Duration::ZERO;
+		p.max_retries = MaxRetries::Finite(0);
+
+		let rs = p
+			.post_batch::<_, _, Value>(vec![(p.uri_of("m"), ()), (p.uri_of("n"), ())])
+			.await
+			.unwrap()
+			.into_iter()
+			.map(|r| r.map(|r| r["response"]["result"].clone()))
+			.collect::<Vec<_>>();
+
+		(p, rs)
+	};
+
+	Runtime::new().unwrap().block_on(async {
+		// Retry what failed for a retryable reason on the next endpoint.
+		let (_, rs) =
+			batch(vec![Fake::new("a", 10, Some(|| Error::Timeout)), Fake::new("b", 10, None)])
+				.await;
+
+		assert_eq!(rs.into_iter().map(Result::unwrap).collect::<Vec<_>>(), ["b", "b"]);
+
+		// Don't retry what a server refused.
+		let (p, rs) = batch(vec![
+			Fake::new("a", 10, Some(|| Error::Server { code: 1, message: "bad".into() })),
+			Fake::new("b", 8, None),
+		])
+		.await;
+
+		assert!(rs.iter().all(|r| matches!(r, Err(Error::Server { code: 1, .. }))));
+		assert_eq!(p.members[1].client.calls.load(Ordering::SeqCst), 0);
+
+		// Refuse a lagging answer even if it's the only one.
+		let (_, rs) = batch(vec![
+			Fake::new("a", 8, None),
+			Fake::new("b", 10, Some(|| Error::ConnectionClosed)),
+		])
+		.await;
+
+		assert!(rs.iter().all(|r| matches!(
+			r,
+			Err(Error::ExceededMaximumRetries(e)) if matches!(**e, Error::Lagging { height: 8, .. })
+		)));
+	});
+}
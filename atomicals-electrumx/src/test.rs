@@ -1,41 +1,681 @@
 // std
-use std::future::Future;
+use std::sync::{Arc, Mutex};
 // crates.io
 use tokio::runtime::Runtime;
 // atomicals-electrumx
-use crate::*;
+use crate::{
+	mock::{Ft, Mock},
+	*,
+};
 
 fn test<F, Fut>(f: F)
 where
-	F: FnOnce(ElectrumX) -> Fut,
+	F: FnOnce(ElectrumX, Mock) -> Fut,
 	Fut: Future<Output = ()>,
 {
-	tracing_subscriber::fmt::init();
+	let _ = tracing_subscriber::fmt::try_init();
 
-	let e = ElectrumXBuilder::testnet().build().unwrap();
+	Runtime::new().unwrap().block_on(async {
+		let m = Mock::start().await;
 
-	Runtime::new().unwrap().block_on(f(e));
+		m.state().add_ft(Ft::easy("atomicalsir"));
+
+		f(ElectrumXBuilder::testnet().base_uri(&m.uri).build().unwrap(), m).await
+	});
 }
 
 #[test]
 fn get_by_ticker_should_work() {
-	test(|e| async move {
-		e.get_by_ticker("atomicalsir").await.unwrap();
+	test(|e, _| async move {
+		assert_eq!(e.get_by_ticker("atomicalsir").await.unwrap().status, "verified");
+		assert!(e.get_by_ticker("sir").await.unwrap().atomical_id.is_none());
 	});
 }
 
 #[test]
 fn get_ft_info_should_work() {
-	test(|e| async move {
-		e.get_ft_info(e.get_by_ticker("atomicalsir").await.unwrap().atomical_id).await.unwrap();
+	test(|e, _| async move {
+		let ft = e
+			.get_ft_info(e.get_by_ticker("atomicalsir").await.unwrap().atomical_id.unwrap())
+			.await
+			.unwrap()
+			.result;
+
+		assert_eq!(ft.ticker, "atomicalsir");
+		assert_eq!(ft.mint_amount, 1_000);
 	});
 }
 
 #[test]
 fn get_unspent_address_should_work() {
-	test(|e| async move {
-		e.get_unspent_address("tb1pemen3j4wvlryktkqsew8ext7wnsgqhmuzl7267rm3xk0th3gh04qr9wcec")
+	test(|e, m| async move {
+		let a = "tb1pemen3j4wvlryktkqsew8ext7wnsgqhmuzl7267rm3xk0th3gh04qr9wcec";
+
+		assert!(e.get_unspent_address(a).await.unwrap().is_empty());
+
+		let u = m
+			.state()
+			.fund(&Address::from_str(a).unwrap().require_network(Network::Testnet).unwrap(), 1_000);
+		let us = e.get_unspent_address(a).await.unwrap();
+
+		assert_eq!((us[0].txid.as_str(), us[0].value), (u.txid.as_str(), 1_000));
+
+		let b = "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx";
+		let us = e.get_unspent_addresses(vec![b, a]).await.unwrap();
+
+		assert!(us[0].is_empty());
+		assert_eq!(us[1][0].txid, u.txid);
+	});
+}
+
+// Answer each connection with the next canned response.
+async fn stub<S>(responses: Vec<(u16, S)>) -> String
+where
+	S: Into<String>,
+{
+	recorder(responses).await.0
+}
+
+// Like `stub`, also recording the requests.
+async fn recorder<S>(responses: Vec<(u16, S)>) -> (String, Arc<Mutex<Vec<String>>>)
+where
+	S: Into<String>,
+{
+	// crates.io
+	use tokio::{
+		io::{AsyncReadExt, AsyncWriteExt},
+		net::TcpListener,
+	};
+
+	let l = TcpListener::bind("127.0.0.1:0").await.unwrap();
+	let uri = format!("http://{}", l.local_addr().unwrap());
+	let requests = Arc::new(Mutex::new(Vec::new()));
+	let requests_ = requests.clone();
+	let responses = responses
+		.into_iter()
+		.map(|(status, body)| (status, body.into()))
+		.collect::<Vec<(_, String)>>();
+
+	tokio::spawn(async move {
+		for (status, body) in responses {
+			let (mut s, _) = l.accept().await.unwrap();
+			let mut req = Vec::new();
+			let mut buf = [0; 1024];
+
+			// Read the whole request before answering.
+			loop {
+				let n = s.read(&mut buf).await.unwrap();
+
+				req.extend_from_slice(&buf[..n]);
+
+				let r = String::from_utf8_lossy(&req);
+
+				if let Some(i) = r.find("\r\n\r\n") {
+					let len = r
+						.lines()
+						.find_map(|l| {
+							l.to_lowercase().strip_prefix("content-length: ")?.parse().ok()
+						})
+						.unwrap_or(0);
+
+					if req.len() >= i + 4 + len {
+						break;
+					}
+				}
+			}
+
+			requests_.lock().unwrap().push(String::from_utf8_lossy(&req).into_owned());
+
+			s.write_all(
+				format!(
+					"HTTP/1.1 {status} X\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+					body.len()
+				)
+				.as_bytes(),
+			)
 			.await
 			.unwrap();
+		}
+	});
+
+	(uri, requests)
+}
+
+#[test]
+fn post_should_work() {
+	Runtime::new().unwrap().block_on(async {
+		let e = |uri| {
+			ElectrumXBuilder::default()
+				.retry_period(Duration::from_millis(10))
+				.max_retries(MaxRetries::Finite(1))
+				.base_uri(uri)
+				.build()
+				.unwrap()
+		};
+
+		// Retry on a bad gateway.
+		let r =
+			e(stub(vec![(502, "bad gateway"), (200, r#"{"success":true,"response":"00"}"#)]).await)
+				.get_transaction("00")
+				.await;
+
+		assert_eq!(r.unwrap(), "00");
+
+		// Give up once the retries are used up.
+		let r = e(stub(vec![(502, "bad gateway"), (503, "unavailable")]).await)
+			.get_transaction("00")
+			.await;
+
+		assert!(matches!(
+			r,
+			Err(Error::ExceededMaximumRetries(e)) if matches!(*e, Error::Status { status: 503, .. })
+		));
+
+		// Don't retry what the server rejected.
+		let r = e(stub(vec![(200, r#"{"success":false,"code":1,"message":"bad request"}"#)]).await)
+			.get_transaction("00")
+			.await;
+
+		assert!(matches!(r, Err(Error::Server { code: 1, .. })));
+
+		let r = e(stub(vec![(404, "not found")]).await).get_transaction("00").await;
+
+		assert!(matches!(r, Err(Error::Status { status: 404, .. })));
+
+		let r = e(stub(vec![(200, "<html></html>")]).await).get_transaction("00").await;
+
+		assert!(matches!(r, Err(Error::Decode { body, .. }) if body == "<html></html>"));
+	});
+}
+
+#[test]
+fn http_options_should_work() {
+	Runtime::new().unwrap().block_on(async {
+		let ok = r#"{"success":true,"response":"00"}"#;
+		let e = |uri, http| {
+			ElectrumXBuilder::default()
+				.retry_period(Duration::from_millis(10))
+				.max_retries(MaxRetries::Finite(1))
+				.base_uri(uri)
+				.http(http)
+				.build()
+		};
+
+		// Back off on a rate limit, sending the headers each time.
+		let (uri, rs) = recorder(vec![(429, "slow down"), (200, ok)]).await;
+		let http = HttpOptions::default().header("X-API-Key", "secret").rate_limit(100);
+
+		assert_eq!(e(uri, http).unwrap().get_transaction("00").await.unwrap(), "00");
+
+		let rs = rs.lock().unwrap().clone();
+
+		assert_eq!(rs.len(), 2);
+		assert!(rs.iter().all(|r| r.to_lowercase().contains("x-api-key: secret")));
+
+		// Go through the proxy instead of the endpoint itself.
+		let (uri, rs) = recorder(vec![(200, ok)]).await;
+		let http = HttpOptions::default().proxy(uri);
+
+		assert_eq!(
+			e("http://electrumx.invalid/proxy".into(), http)
+				.unwrap()
+				.get_transaction("00")
+				.await
+				.unwrap(),
+			"00"
+		);
+		assert!(rs.lock().unwrap()[0]
+			.starts_with("POST http://electrumx.invalid/proxy/blockchain.transaction.get "));
+
+		let http = HttpOptions::default().header("X-API-Key", "\n");
+
+		assert!(matches!(e(String::new(), http), Err(Error::InvalidHeader(_))));
+
+		let http = HttpOptions::default().ca_certificate("/nonexistent.pem");
+
+		assert!(matches!(e(String::new(), http), Err(Error::Io(_))));
+	});
+}
+
+#[test]
+fn broadcast_should_work() {
+	// One input and one output, both empty.
+	let tx = format!("0200000001{}00000000{}{}", "00".repeat(32), "00ffffffff01", "00".repeat(13));
+	let txid = encode::deserialize::<Transaction>(&Vec::from_hex(&tx).unwrap()).unwrap().txid();
+
+	Runtime::new().unwrap().block_on(async {
+		let e = |body| async move {
+			ElectrumXBuilder::default().base_uri(stub(vec![(200, body)]).await).build().unwrap()
+		};
+
+		assert_eq!(
+			e(r#"{"success":true,"response":"00"}"#).await.broadcast(&tx).await.unwrap(),
+			txid
+		);
+		assert_eq!(
+			e(r#"{"success":false,"code":1,"message":"txn-already-known"}"#)
+				.await
+				.broadcast(&tx)
+				.await
+				.unwrap(),
+			txid
+		);
+		assert!(matches!(
+			e(
+				r#"{"success":false,"code":1,"message":"too-long-mempool-chain, too many descendants"}"#
+			)
+			.await
+			.broadcast(&tx)
+			.await,
+			Err(Error::Rejected { reason: Rejection::TooLongMempoolChain, .. })
+		));
+		assert!(matches!(
+			e(r#"{"success":false,"code":1,"message":"bad-txns-vout-negative"}"#)
+				.await
+				.broadcast(&tx)
+				.await,
+			Err(Error::Server { code: 1, .. })
+		));
+	});
+}
+
+#[test]
+fn send_raw_transaction_should_work() {
+	// atomicals-electrumx
+	use bitcoind::BitcoindBuilder;
+
+	let tx = format!("0200000001{}00000000{}{}", "00".repeat(32), "00ffffffff01", "00".repeat(13));
+	let txid = txid_of(&tx).unwrap();
+
+	Runtime::new().unwrap().block_on(async {
+		let b = |status, body| async move {
+			let uri = stub(vec![(status, body)]).await.replace("http://", "http://user:pass@");
+
+			BitcoindBuilder::default().uri(uri).build().unwrap()
+		};
+		let r = b(200, r#"{"result":"00","error":null,"id":0}"#).await;
+
+		assert!(!r.name().contains("pass"));
+		assert_eq!(r.send_raw_transaction(&tx).await.unwrap(), txid);
+		assert_eq!(
+			b(
+				500,
+				r#"{"result":null,"error":{"code":-27,"message":"Transaction already in block chain"},"id":0}"#
+			)
+			.await
+			.send_raw_transaction(&tx)
+			.await
+			.unwrap(),
+			txid
+		);
+		assert!(matches!(
+			b(
+				500,
+				r#"{"result":null,"error":{"code":-26,"message":"min relay fee not met"},"id":0}"#
+			)
+			.await
+			.send_raw_transaction(&tx)
+			.await,
+			Err(Error::Rejected { reason: Rejection::InsufficientFee, .. })
+		));
+		assert!(matches!(
+			b(401, "").await.send_raw_transaction(&tx).await,
+			Err(Error::Status { status: 401, .. })
+		));
+	});
+}
+
+#[test]
+fn bitcoind_chain_should_work() {
+	// atomicals-electrumx
+	use bitcoind::BitcoindBuilder;
+	use chain::Chain;
+
+	let tx = format!("0200000001{}00000000{}{}", "00".repeat(32), "00ffffffff01", "00".repeat(13));
+	let cookie =
+		std::env::temp_dir().join(format!("atomicals-electrumx-{}.cookie", std::process::id()));
+
+	std::fs::write(&cookie, "__cookie__:abc\n").unwrap();
+
+	Runtime::new().unwrap().block_on(async {
+		let (uri, requests) = recorder(vec![
+			(200, r#"{"result":{"descriptor":"addr(bc1q)#00000000"},"error":null,"id":0}"#),
+			(200, r#"{"result":[{"success":true}],"error":null,"id":0}"#),
+			// A UTXO which predates the import and one the wallet knows about as well.
+			(
+				200,
+				r#"{"result":{"success":true,"unspents":[{"txid":"03","vout":2,"amount":0.00003},{"txid":"01","vout":1,"amount":0.0001}]},"error":null,"id":0}"#,
+			),
+			(200, r#"{"result":[{"txid":"01","vout":1,"amount":0.0001}],"error":null,"id":0}"#),
+			(200, r#"{"result":{"value":0.00003},"error":null,"id":0}"#),
+			(
+				200,
+				r#"{"result":[{"txid":"01","vout":1,"amount":0.0001},{"txid":"02","vout":0,"amount":0.00000546}],"error":null,"id":0}"#,
+			),
+			// Spent since.
+			(200, r#"{"result":null,"error":null,"id":0}"#),
+			(200, r#"{"result":{"feerate":0.00012345,"blocks":2},"error":null,"id":0}"#),
+			(200, r#"{"result":{"errors":["Insufficient data"],"blocks":0},"error":null,"id":0}"#),
+			(200, r#"{"result":[{"allowed":true}],"error":null,"id":0}"#),
+			(200, r#"{"result":[{"allowed":false,"reject-reason":"dust"}],"error":null,"id":0}"#),
+		])
+		.await;
+		let b = BitcoindBuilder::default()
+			.uri(uri)
+			.cookie(&cookie)
+			.wait_timeout(Some(Duration::from_millis(10)))
+			.build()
+			.unwrap();
+
+		// Import on first use only.
+		let utxos = b.utxos("bc1q").await.unwrap();
+
+		assert_eq!(
+			utxos.iter().map(|u| (u.txid.as_str(), u.vout, u.value)).collect::<Vec<_>>(),
+			[("03", 2, 3_000), ("01", 1, 10_000)]
+		);
+		assert_eq!(b.wait_for_utxo("bc1q", 1_000).await.unwrap().txid, "01");
+		assert_eq!(b.fee_rate(2).await.unwrap(), 13);
+		assert!(matches!(b.fee_rate(2).await, Err(Error::Server { message, .. }) if message == "Insufficient data"));
+		Chain::test_mempool_accept(&b, &tx).await.unwrap();
+		assert!(matches!(
+			Chain::test_mempool_accept(&b, &tx).await,
+			Err(Error::Rejected { reason: Rejection::Dust, .. })
+		));
+
+		let requests = requests.lock().unwrap();
+		let methods = requests
+			.iter()
+			.map(|r| {
+				assert!(r.contains("X19jb29raWVfXzphYmM="));
+
+				r.split(r#""method":""#).nth(1).unwrap().split('"').next().unwrap()
+			})
+			.collect::<Vec<_>>();
+
+		assert_eq!(
+			methods,
+			[
+				"getdescriptorinfo",
+				"importdescriptors",
+				"scantxoutset",
+				"listunspent",
+				"gettxout",
+				"listunspent",
+				"gettxout",
+				"estimatesmartfee",
+				"estimatesmartfee",
+				"testmempoolaccept",
+				"testmempoolaccept"
+			]
+		);
+	});
+
+	std::fs::remove_file(cookie).unwrap();
+}
+
+#[test]
+fn bitcoind_wait_for_tx_out_should_work() {
+	// atomicals-electrumx
+	use bitcoind::BitcoindBuilder;
+
+	Runtime::new().unwrap().block_on(async {
+		let (uri, requests) = recorder(vec![
+			// A commit output which is only in the mempool.
+			(
+				200,
+				r#"{"result":{"bestblock":"00","confirmations":0,"value":0.00010546,"scriptPubKey":{"hex":"51200000"},"coinbase":false},"error":null,"id":0}"#,
+			),
+			// Not relayed yet, before and after the last poll.
+			(200, r#"{"result":null,"error":null,"id":0}"#),
+			(200, r#"{"result":null,"error":null,"id":0}"#),
+		])
+		.await;
+		let b = BitcoindBuilder::default()
+			.uri(uri)
+			.wait_timeout(Some(Duration::from_millis(10)))
+			.build()
+			.unwrap();
+		let u = b.wait_for_tx_out("0a", 0).await.unwrap();
+
+		assert_eq!((u.txid.as_str(), u.vout, u.value), ("0a", 0, 10_546));
+		assert!(matches!(
+			b.wait_for_tx_out("0b", 0).await,
+			Err(Error::TxOutWaitTimeout(o)) if o == "0b:0"
+		));
+
+		// No import, no scan, the mempool included.
+		let requests = requests.lock().unwrap();
+
+		assert_eq!(requests.len(), 3);
+		assert!(requests[0].contains(r#""method":"gettxout","params":["0a",0,true]"#));
+	});
+}
+
+#[test]
+fn wait_for_plain_utxo_should_work() {
+	// atomicals-electrumx
+	use bitcoind::BitcoindBuilder;
+	use chain::wait_for_plain_utxo;
+	use esplora::EsploraBuilder;
+
+	test(|e, m| async move {
+		let a = "tb1pemen3j4wvlryktkqsew8ext7wnsgqhmuzl7267rm3xk0th3gh04qr9wcec";
+		let address = Address::from_str(a).unwrap().require_network(Network::Testnet).unwrap();
+		let id = m.state().tickers["atomicalsir"];
+		let ft = m.state().fund_atomicals(&address, 2_000, &[id]);
+		let plain = m.state().fund(&address, 10_000);
+		let unspent = |us: &[(&str, u64)]| {
+			let us = us
+				.iter()
+				.map(|(txid, value)| {
+					format!(r#"{{"txid":"{txid}","vout":0,"amount":{}}}"#, *value as f64 / 1e8)
+				})
+				.collect::<Vec<_>>()
+				.join(",");
+
+			(200, format!(r#"{{"result":[{us}],"error":null,"id":0}}"#))
+		};
+		let uri = stub(vec![
+			(200, r#"{"result":{"descriptor":"addr(tb1p)#00000000"},"error":null,"id":0}"#.into()),
+			(200, r#"{"result":[{"success":true}],"error":null,"id":0}"#.into()),
+			(200, r#"{"result":{"success":true,"unspents":[]},"error":null,"id":0}"#.into()),
+			// Bitcoind sees the FT and a UTXO which isn't indexed yet, both smaller.
+			unspent(&[(&ft.txid, 2_000), (&"ff".repeat(32), 5_000), (&plain.txid, 10_000)]),
+			unspent(&[(&ft.txid, 2_000)]),
+		])
+		.await;
+		let b = BitcoindBuilder::default().uri(uri).build().unwrap();
+
+		assert_eq!(
+			wait_for_plain_utxo(&b, &e, a, 1_000, Some(Duration::ZERO)).await.unwrap().txid,
+			plain.txid
+		);
+		assert!(matches!(
+			wait_for_plain_utxo(&b, &e, a, 1_000, Some(Duration::ZERO)).await,
+			Err(Error::WaitTimeout { .. })
+		));
+
+		// The same goes for Esplora.
+		let uri = stub(vec![(
+			200,
+			format!(
+				r#"[{{"txid":"{}","vout":0,"status":{{"confirmed":true}},"value":2000}},{{"txid":"{}","vout":0,"status":{{"confirmed":true}},"value":10000}}]"#,
+				ft.txid, plain.txid
+			),
+		)])
+		.await;
+		let s = EsploraBuilder::default().uri(uri).build().unwrap();
+
+		assert_eq!(
+			wait_for_plain_utxo(&s, &e, a, 1_000, Some(Duration::ZERO)).await.unwrap().txid,
+			plain.txid
+		);
+	});
+}
+
+#[test]
+fn esplora_should_work() {
+	// atomicals-electrumx
+	use chain::Chain;
+	use esplora::EsploraBuilder;
+
+	let tx = format!("0200000001{}00000000{}{}", "00".repeat(32), "00ffffffff01", "00".repeat(13));
+	let txid = txid_of(&tx).unwrap();
+
+	Runtime::new().unwrap().block_on(async {
+		let (uri, requests) = recorder(vec![
+			(
+				200,
+				r#"[{"txid":"01","vout":0,"status":{"confirmed":false},"value":2000},{"txid":"02","vout":1,"status":{"confirmed":true,"block_height":1},"value":1000}]"#,
+			),
+			(200, r#"{"confirmed":true,"block_height":1,"block_hash":"00","block_time":1}"#),
+			(200, r#"{"1":20.1,"3":10.5,"144":1.0}"#),
+			(200, r#"{"1":20.1,"3":10.5,"144":1.0}"#),
+			// The txid is computed locally.
+			(200, "00"),
+			(
+				400,
+				r#"sendrawtransaction RPC error: {"code":-26,"message":"min relay fee not met"}"#,
+			),
+			(502, "bad gateway"),
+		])
+		.await;
+		let e = EsploraBuilder::default().uri(format!("{uri}/api/")).build().unwrap();
+		let utxos = e.utxos("bc1q").await.unwrap();
+
+		assert_eq!((utxos[0].txid.as_str(), utxos[0].value), ("02", 1000));
+		assert_eq!(e.tx_status("01").await.unwrap().block_height, Some(1));
+		assert_eq!(e.fee_rate(2).await.unwrap(), 21);
+		assert_eq!(e.fee_rate(6).await.unwrap(), 11);
+		assert_eq!(e.send_transaction(&tx).await.unwrap(), txid);
+		assert!(matches!(
+			e.send_transaction(&tx).await,
+			Err(Error::Rejected { reason: Rejection::InsufficientFee, .. })
+		));
+		assert!(matches!(
+			e.send_transaction(&tx).await,
+			Err(Error::Status { status: 502, .. })
+		));
+
+		let requests = requests.lock().unwrap();
+		let lines = requests.iter().map(|r| r.lines().next().unwrap()).collect::<Vec<_>>();
+
+		assert_eq!(lines[0], "GET /api/address/bc1q/utxo HTTP/1.1");
+		assert_eq!(lines[1], "GET /api/tx/01/status HTTP/1.1");
+		assert_eq!(lines[2], "GET /api/fee-estimates HTTP/1.1");
+		assert_eq!(lines[4], "POST /api/tx HTTP/1.1");
+		assert!(requests[4].ends_with(&tx));
+	});
+}
+
+#[test]
+fn api_should_work() {
+	let zeros = "0".repeat(64);
+	let id = format!("{zeros}i0");
+	let genesis = "0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c";
+	let responses = [
+		serde_json::json!({ "result": {
+			"atomical_id": id, "atomical_number": 1, "type": "NFT",
+			"location_info": [{
+				"location": format!("{zeros}i1"), "txid": zeros, "index": 1, "scripthash": "00",
+				"value": 546, "script": "51", "atomicals_at_location": [id], "tx_num": 7
+			}]
+		}}),
+		serde_json::json!({
+			"global": null,
+			"atomicals": { &id: {
+				"atomical_id": id, "atomical_number": 1, "type": "FT", "confirmed": true,
+				"ticker": "atomicalsir"
+			}},
+			"utxos": [{ "txid": zeros, "vout": 1, "height": 1, "value": 546, "atomicals": [id] }]
+		}),
+		serde_json::json!({ "result": {
+			"atomical_id": id, "type": "FT",
+			"holders": [{ "percent": 0.5, "address": "bc1q", "holding": 1000 }]
+		}}),
+		serde_json::json!({ "result": [{ "ticker": "atomicalsir", "atomical_id": id, "tx_num": 7 }] }),
+		serde_json::json!({ "result": {
+			"container": "#c", "atomical_id": id,
+			"items": { "0": { "atomical_id": id, "status": "verified" } }
+		}}),
+		serde_json::json!(0.0002),
+		serde_json::json!(genesis),
+	]
+	.map(|r| serde_json::json!({ "success": true, "response": r }).to_string());
+
+	Runtime::new().unwrap().block_on(async {
+		let e = ElectrumXBuilder::default()
+			.base_uri(stub(responses.into_iter().map(|r| (200, r)).collect()).await)
+			.build()
+			.unwrap();
+		let id = id.parse::<r#type::AtomicalId>().unwrap();
+		let l = e.get_location(id.to_string()).await.unwrap().result;
+
+		assert_eq!(l.location_info[0].atomicals_at_location, [id]);
+
+		let s = e.list_scripthash("00").await.unwrap();
+
+		assert_eq!(s.atomicals[&id].r#type, r#type::AtomicalType::Ft);
+		assert!(Utxo::from(s.utxos.into_iter().next().unwrap()).carries(&id));
+		assert_eq!(
+			e.get_holders(id.to_string(), 10, 0).await.unwrap().result.holders[0].holding,
+			1000
+		);
+		assert_eq!(
+			e.find_tickers("atomicals", true, 10, 0).await.unwrap().result[0].atomical_id,
+			id
+		);
+		assert_eq!(
+			e.get_container_items("#c", 10, 0).await.unwrap().result.items["0"].atomical_id,
+			Some(id)
+		);
+		assert_eq!(e.estimate_fee(1).await.unwrap(), 0.0002);
+		assert_eq!(
+			e.get_block_header(0).await.unwrap().block_hash().to_string(),
+			"000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f"
+		);
+	});
+}
+
+// Refresh the fixtures with the responses of the public proxies.
+//
+// Run with `cargo test -p atomicals-electrumx capture_fixtures -- --ignored`.
+#[test]
+#[ignore = "needs network access"]
+fn capture_fixtures() {
+	// crates.io
+	use serde::Serialize;
+	use serde_json::{ser::PrettyFormatter, Serializer, Value};
+
+	Runtime::new().unwrap().block_on(async {
+		for (e, name, ticker) in [
+			(ElectrumXBuilder::default().build().unwrap(), "mainnet", "atom"),
+			(ElectrumXBuilder::testnet().build().unwrap(), "testnet", "atom"),
+		] {
+			let id = e.get_by_ticker(ticker).await.unwrap().atomical_id.unwrap();
+			let r = e
+				.post::<_, _, Value>(e.uri_of("blockchain.atomicals.get_ft_info"), [&id])
+				.await
+				.unwrap();
+			let mut json = Vec::new();
+
+			r.serialize(&mut Serializer::with_formatter(
+				&mut json,
+				PrettyFormatter::with_indent(b"\t"),
+			))
+			.unwrap();
+			json.push(b'\n');
+			std::fs::write(
+				format!("{}/fixture/get_ft_info-{name}.json", env!("CARGO_MANIFEST_DIR")),
+				json,
+			)
+			.unwrap();
+
+			println!(
+				"captured the FT info of {ticker} ({id}) on {name} at height {}",
+				r["response"]["global"]["height"]
+			);
+		}
 	});
 }
@@ -0,0 +1,156 @@
+//! HTTP transport settings of the electrumx proxy and Esplora clients.
+
+// std
+use std::{fs, path::PathBuf, time::Duration};
+// crates.io
+use reqwest::{
+	header::{HeaderMap, HeaderName, HeaderValue},
+	Certificate, ClientBuilder as ReqwestClientBuilder, Proxy,
+};
+use tokio::{
+	sync::Mutex,
+	time::{self, Instant},
+};
+// atomicals-electrumx
+use crate::prelude::*;
+
+/// Settings of the underlying HTTP client.
+///
+/// An electrum server at `tcp://` or `ssl://` takes them as well, except that the proxy must be a
+/// SOCKS5 one and there are no headers to send.
+#[derive(Clone, Debug, Default)]
+pub struct HttpOptions {
+	/// Proxy for all requests, e.g. `socks5h://127.0.0.1:9050` for a local Tor daemon.
+	pub proxy: Option<String>,
+	/// Headers sent with every request, e.g. an API key.
+	pub headers: Vec<(String, String)>,
+	/// PEM files of extra root certificates to trust.
+	pub ca_certificates: Vec<PathBuf>,
+	/// Minimum interval between two requests to the same endpoint.
+	pub min_interval: Option<Duration>,
+}
+impl HttpOptions {
+	/// Set proxy.
+	pub fn proxy<S>(mut self, proxy: S) -> Self
+	where
+		S: Into<String>,
+	{
+		self.proxy = Some(proxy.into());
+
+		self
+	}
+
+	/// Add a header.
+	pub fn header<N, V>(mut self, name: N, value: V) -> Self
+	where
+		N: Into<String>,
+		V: Into<String>,
+	{
+		self.headers.push((name.into(), value.into()));
+
+		self
+	}
+
+	/// Add a PEM file of root certificates.
+	pub fn ca_certificate<P>(mut self, path: P) -> Self
+	where
+		P: Into<PathBuf>,
+	{
+		self.ca_certificates.push(path.into());
+
+		self
+	}
+
+	/// Limit the requests to the given number per second.
+	pub fn rate_limit(mut self, per_second: u32) -> Self {
+		self.min_interval = (per_second != 0).then(|| Duration::from_secs(1) / per_second);
+
+		self
+	}
+
+	/// Apply the settings to a reqwest client builder.
+	pub fn apply(&self, mut builder: ReqwestClientBuilder) -> Result<ReqwestClientBuilder> {
+		if let Some(p) = &self.proxy {
+			builder = builder.proxy(Proxy::all(p)?);
+		}
+		if !self.headers.is_empty() {
+			let mut hs = HeaderMap::new();
+
+			for (n, v) in &self.headers {
+				let invalid = || Error::InvalidHeader(format!("{n}: {v}"));
+
+				hs.append(
+					HeaderName::from_bytes(n.as_bytes()).map_err(|_| invalid())?,
+					HeaderValue::from_str(v).map_err(|_| invalid())?,
+				);
+			}
+
+			builder = builder.default_headers(hs);
+		}
+		for p in &self.ca_certificates {
+			for c in Certificate::from_pem_bundle(&fs::read(p)?)? {
+				builder = builder.add_root_certificate(c);
+			}
+		}
+
+		Ok(builder)
+	}
+
+	/// Build the rate limiter, if any.
+	pub fn rate_limiter(&self) -> Option<RateLimiter> {
+		self.min_interval.map(RateLimiter::new)
+	}
+}
+
+/// Client side rate limiter, spacing the requests evenly.
+#[derive(Debug)]
+pub struct RateLimiter {
+	interval: Duration,
+	next: Mutex<Instant>,
+}
+impl RateLimiter {
+	/// Allow one request per `interval`.
+	pub fn new(interval: Duration) -> Self {
+		Self { interval, next: Mutex::new(Instant::now()) }
+	}
+
+	/// Wait for the next free slot.
+	pub async fn acquire(&self) {
+		let mut next = self.next.lock().await;
+		let now = Instant::now();
+
+		if *next > now {
+			time::sleep_until(*next).await;
+		}
+
+		*next = (*next).max(now) + self.interval;
+	}
+}
+
+/// Delay before the next attempt after being rate limited.
+///
+/// Double `base` on each attempt up to a minute, then pick a random delay between the half and
+/// the whole of it so the clients don't come back all at once. Never return less than the
+/// server's `Retry-After`.
+pub fn backoff(base: Duration, attempt: u32, retry_after: Option<Duration>) -> Duration {
+	let d = base.saturating_mul(1 << attempt.min(16)).min(Duration::from_secs(60));
+	let d = d.mul_f64(0.5 + bitcoin::secp256k1::rand::random::<f64>() / 2.);
+
+	retry_after.map_or(d, |r| d.max(r))
+}
+#[test]
+fn backoff_should_work() {
+	let s = Duration::from_secs;
+
+	for _ in 0..100 {
+		let d = backoff(s(1), 0, None);
+
+		assert!(d >= Duration::from_millis(500) && d <= s(1));
+
+		let d = backoff(s(1), 3, None);
+
+		assert!(d >= s(4) && d <= s(8));
+		assert!(backoff(s(1), 30, None) <= s(60));
+		assert!(backoff(s(1), 0, Some(s(10))) == s(10));
+	}
+}
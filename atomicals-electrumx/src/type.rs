@@ -1,23 +1,42 @@
 #![allow(missing_docs)]
 
 // std
-use std::collections::HashMap;
+use std::{
+	collections::{BTreeMap, HashMap},
+	fmt::{Display, Formatter, Result as FmtResult},
+	str::FromStr,
+};
 // crates.io
-use serde::Deserialize;
+use bitcoin::{hashes::Hash, Txid};
+use serde::{de::Error as DeError, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+// atomicals-electrumx
+use crate::prelude::*;
 
-// TODO: Handle errors.
 #[derive(Debug, Deserialize)]
 pub struct Response<R> {
 	pub success: bool,
 	pub response: R,
 }
 
+// The proxy reports server-side errors with `success: false`.
+#[derive(Debug, Deserialize)]
+pub struct Failure {
+	pub success: bool,
+	pub code: Option<i64>,
+	pub message: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ResponseResult<R> {
 	pub global: Option<Global>,
 	pub result: R,
 }
 #[derive(Debug, Deserialize)]
+pub struct GlobalInfo {
+	pub global: Global,
+}
+#[derive(Debug, Deserialize)]
 pub struct Global {
 	pub atomical_count: u64,
 	pub atomicals_block_hashes: HashMap<String, String>,
@@ -32,8 +51,8 @@ pub struct Global {
 #[derive(Debug, Deserialize)]
 pub struct Ticker {
 	pub status: String,
-	pub candidate_atomical_id: String,
-	pub atomical_id: String,
+	pub candidate_atomical_id: Option<String>,
+	pub atomical_id: Option<String>,
 	pub candidates: Vec<Candidate>,
 	pub r#type: String,
 }
@@ -126,20 +145,38 @@ pub struct Args {
 	pub mint_bitworkc: String,
 	pub mint_bitworkr: Option<String>,
 	pub mint_height: u64,
-	// TODO: It's a `String` in mainnet but a `u64` in testnet.
-	// pub nonce: u64,
+	// A string on mainnet but a number on testnet.
+	#[serde(default, deserialize_with = "lenient_u64")]
+	pub nonce: Option<u64>,
 	pub request_ticker: String,
+	#[serde(deserialize_with = "lenient_u64_required")]
 	pub time: u64,
 }
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 pub struct Meta {
 	pub description: Option<String>,
 	pub legal: Option<Legal>,
+	#[serde(default)]
+	pub links: BTreeMap<String, Link>,
 	pub name: Option<String>,
 }
 #[derive(Debug, Deserialize)]
 pub struct Legal {
-	pub terms: String,
+	pub terms: Option<String>,
+	pub license: Option<String>,
+}
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum Link {
+	Value { v: String },
+	Uri(String),
+}
+impl Link {
+	pub fn uri(&self) -> &str {
+		match self {
+			Self::Value { v } | Self::Uri(v) => v,
+		}
+	}
 }
 #[derive(Debug, Deserialize)]
 pub struct MintInfo {
@@ -157,8 +194,10 @@ pub struct MintInfo {
 	pub commit_location: String,
 	pub commit_tx_num: u64,
 	pub commit_txid: String,
+	#[serde(default)]
 	pub ctx: Ctx,
-	pub meta: Meta,
+	#[serde(default)]
+	pub meta: Option<Meta>,
 	pub reveal_location: String,
 	pub reveal_location_blockhash: String,
 	pub reveal_location_header: String,
@@ -170,9 +209,250 @@ pub struct MintInfo {
 	pub reveal_location_txid: String,
 	pub reveal_location_value: u64,
 }
-// TODO: Check the real type.
+// The payload defines it freely.
+#[derive(Debug, Default, Deserialize)]
+pub struct Ctx {
+	#[serde(flatten)]
+	pub fields: BTreeMap<String, Value>,
+}
+
+// Accept a number, a numeric string or nothing.
+fn lenient_u64<'de, D>(deserializer: D) -> StdResult<Option<u64>, D::Error>
+where
+	D: Deserializer<'de>,
+{
+	#[derive(Deserialize)]
+	#[serde(untagged)]
+	enum Raw {
+		Number(u64),
+		String(String),
+	}
+
+	match Option::<Raw>::deserialize(deserializer)? {
+		None => Ok(None),
+		Some(Raw::Number(n)) => Ok(Some(n)),
+		Some(Raw::String(s)) => s.trim().parse().map(Some).map_err(D::Error::custom),
+	}
+}
+
+fn lenient_u64_required<'de, D>(deserializer: D) -> StdResult<u64, D::Error>
+where
+	D: Deserializer<'de>,
+{
+	lenient_u64(deserializer)?.ok_or_else(|| D::Error::custom("expected a number"))
+}
+#[test]
+fn ft_should_work() {
+	// The fixtures are hand-written in the shape of the `get_ft_info` responses. Replace them with
+	// real ones from `capture_fixtures`, noting the ticker and height it prints here.
+	let ft = |json| serde_json::from_str::<Response<ResponseResult<Ft>>>(json).unwrap().response;
+	let mainnet = ft(include_str!("../fixture/get_ft_info-mainnet.json"));
+	let testnet = ft(include_str!("../fixture/get_ft_info-testnet.json"));
+
+	assert_eq!(mainnet.global.unwrap().network, "mainnet");
+
+	let mainnet = mainnet.result;
+	let meta = mainnet.mint_data.fields.meta.unwrap();
+
+	assert_eq!(mainnet.mint_data.fields.args.nonce, Some(8_834_175));
+	assert_eq!(meta.legal.unwrap().terms.as_deref(), Some("All rights reserved."));
+	assert_eq!(meta.links["website"].uri(), "https://hack.ink/atomicalsir");
+	assert_eq!(meta.links["x"].uri(), "https://x.com/hack_ink");
+	assert!(mainnet.mint_info.meta.is_some() && mainnet.mint_info.ctx.fields.is_empty());
+
+	let testnet = testnet.result;
+
+	assert_eq!(testnet.mint_data.fields.args.nonce, Some(8_834_175));
+	assert_eq!(testnet.mint_info.args.nonce, None);
+	assert_eq!(testnet.mint_info.args.time, 1_703_000_000);
+	assert_eq!(testnet.mint_bitworkr.as_deref(), Some("c"));
+	assert!(testnet.mint_data.fields.meta.is_none() && testnet.mint_info.meta.is_none());
+	assert!(testnet.mint_info.ctx.fields.contains_key("image.png"));
+	assert!(serde_json::from_str::<Args>(r#"{"nonce":"x"}"#).is_err());
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Atomical {
+	#[serde(rename = "$request_subrealm_status")]
+	pub request_subrealm_status: Option<NameStatus>,
+	#[serde(default, rename = "$subrealm_candidates")]
+	pub subrealm_candidates: Vec<SubrealmCandidate>,
+	pub atomical_id: String,
+	pub atomical_number: u64,
+	pub atomical_ref: Option<String>,
+	pub confirmed: bool,
+	pub subtype: Option<String>,
+	pub r#type: AtomicalType,
+}
+#[derive(Debug, Deserialize)]
+pub struct AtomicalLocation {
+	pub atomical_id: AtomicalId,
+	pub atomical_number: u64,
+	pub r#type: AtomicalType,
+	#[serde(default)]
+	pub location_info: Vec<Location>,
+}
+#[derive(Debug, Deserialize)]
+pub struct Location {
+	pub location: String,
+	pub txid: String,
+	pub index: u32,
+	pub scripthash: String,
+	pub value: u64,
+	pub script: String,
+	pub address: Option<String>,
+	#[serde(default)]
+	pub atomicals_at_location: Vec<AtomicalId>,
+	pub tx_num: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Holders {
+	pub atomical_id: AtomicalId,
+	pub r#type: AtomicalType,
+	#[serde(default)]
+	pub holders: Vec<Holder>,
+}
+#[derive(Debug, Deserialize)]
+pub struct Holder {
+	pub percent: f64,
+	pub address: Option<String>,
+	pub holding: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TickerEntry {
+	pub ticker: String,
+	pub atomical_id: AtomicalId,
+	pub tx_num: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NameStatus {
+	pub status: String,
+	pub note: Option<String>,
+	pub verified_atomical_id: Option<String>,
+	pub pending_candidate_atomical_id: Option<String>,
+	pub claimed_by_atomical_id: Option<String>,
+}
+#[derive(Debug, Deserialize)]
+pub struct SubrealmCandidate {
+	pub atomical_id: String,
+	pub commit_height: u64,
+	pub reveal_location_height: u64,
+	pub tx_num: u64,
+	pub txid: String,
+	pub payment: Option<String>,
+	pub payment_type: Option<String>,
+	pub make_payment_from_height: Option<u64>,
+	pub payment_due_no_later_than_height: Option<u64>,
+	pub applicable_rule: Option<MintRule>,
+}
+
+pub type Realm = Ticker;
+
+#[derive(Debug, Deserialize)]
+pub struct RealmInfo {
+	pub atomical_id: Option<String>,
+	pub top_level_realm_atomical_id: Option<String>,
+	pub top_level_realm_name: Option<String>,
+	pub nearest_parent_realm_atomical_id: Option<String>,
+	pub nearest_parent_realm_name: Option<String>,
+	pub request_full_realm_name: String,
+	pub found_full_realm_name: Option<String>,
+	pub missing_name_parts: Option<String>,
+	#[serde(default)]
+	pub nearest_parent_realm_subrealm_mint_allowed: bool,
+	pub nearest_parent_realm_subrealm_mint_rules: Option<SubrealmMintRules>,
+}
+#[derive(Debug, Deserialize)]
+pub struct SubrealmMintRules {
+	pub nearest_parent_realm_atomical_id: Option<String>,
+	pub current_height: u64,
+	#[serde(default)]
+	pub current_height_rules: Vec<MintRule>,
+}
+#[derive(Clone, Debug, Deserialize)]
+pub struct MintRule {
+	pub p: String,
+	#[serde(default)]
+	pub o: BTreeMap<String, RuleOutput>,
+	pub bitworkc: Option<String>,
+	pub bitworkr: Option<String>,
+}
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum RuleOutput {
+	Value(u64),
+	Detailed { v: u64, id: Option<String> },
+}
+impl RuleOutput {
+	pub fn value(&self) -> u64 {
+		match self {
+			Self::Value(v) | Self::Detailed { v, .. } => *v,
+		}
+	}
+
+	pub fn atomical_id(&self) -> Option<&str> {
+		match self {
+			Self::Value(_) => None,
+			Self::Detailed { id, .. } => id.as_deref(),
+		}
+	}
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AtomicalState {
+	pub atomical_id: String,
+	pub state: State,
+}
+#[derive(Debug, Deserialize)]
+pub struct State {
+	pub latest: StateLatest,
+}
+#[derive(Debug, Deserialize)]
+pub struct StateLatest {
+	pub dmint: Option<Dmint>,
+}
+#[derive(Debug, Deserialize)]
+pub struct Dmint {
+	pub v: Option<String>,
+	pub mint_height: u64,
+	pub items: u64,
+	pub merkle: String,
+	#[serde(default)]
+	pub immutable: bool,
+	#[serde(default)]
+	pub rules: Vec<MintRule>,
+}
+
+pub type Container = Ticker;
+
+#[derive(Debug, Deserialize)]
+pub struct ContainerItems {
+	pub container: String,
+	pub atomical_id: Option<AtomicalId>,
+	#[serde(default)]
+	pub items: BTreeMap<String, ContainerItem>,
+}
 #[derive(Debug, Deserialize)]
-pub struct Ctx {}
+pub struct ContainerItem {
+	pub atomical_id: Option<AtomicalId>,
+	pub status: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ItemValidation {
+	pub status: Option<String>,
+	pub candidate_atomical_id: Option<String>,
+	pub atomical_id: Option<String>,
+	#[serde(default)]
+	pub candidates: Vec<Candidate>,
+	#[serde(default)]
+	pub proof_valid: bool,
+	pub target_vector: Option<String>,
+	pub target_hash: Option<String>,
+}
 
 #[derive(Debug, Deserialize)]
 pub struct Unspent {
@@ -183,8 +463,58 @@ pub struct Unspent {
 	pub vout: u32,
 	pub height: u64,
 	pub value: u64,
-	// TODO: Check the real type.
-	pub atomicals: Vec<()>,
+	#[serde(deserialize_with = "utxo_atomicals")]
+	pub atomicals: Vec<UtxoAtomical>,
+}
+
+// Older servers list the IDs only, newer ones map each ID to the value it carries.
+fn utxo_atomicals<'de, D>(deserializer: D) -> StdResult<Vec<UtxoAtomical>, D::Error>
+where
+	D: Deserializer<'de>,
+{
+	#[derive(Deserialize)]
+	#[serde(untagged)]
+	enum Raw {
+		Ids(Vec<AtomicalId>),
+		Values(BTreeMap<AtomicalId, u64>),
+	}
+
+	Ok(match Raw::deserialize(deserializer)? {
+		Raw::Ids(ids) => ids.into_iter().map(|id| UtxoAtomical { id, value: None }).collect(),
+		Raw::Values(vs) =>
+			vs.into_iter().map(|(id, v)| UtxoAtomical { id, value: Some(v) }).collect(),
+	})
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScripthashAtomicals {
+	pub global: Option<Global>,
+	#[serde(default)]
+	pub atomicals: BTreeMap<AtomicalId, AtomicalSummary>,
+	#[serde(default)]
+	pub utxos: Vec<ScripthashUnspent>,
+}
+#[derive(Debug, Deserialize)]
+pub struct AtomicalSummary {
+	pub atomical_id: AtomicalId,
+	pub atomical_number: u64,
+	pub r#type: AtomicalType,
+	pub confirmed: bool,
+	pub subtype: Option<String>,
+	pub request_ticker: Option<String>,
+	pub ticker: Option<String>,
+	pub request_realm: Option<String>,
+	pub full_realm_name: Option<String>,
+	pub request_container: Option<String>,
+}
+#[derive(Debug, Deserialize)]
+pub struct ScripthashUnspent {
+	pub txid: String,
+	pub vout: u32,
+	pub height: u64,
+	pub value: u64,
+	#[serde(deserialize_with = "utxo_atomicals")]
+	pub atomicals: Vec<UtxoAtomical>,
 }
 
 #[derive(Clone, Debug)]
@@ -193,10 +523,252 @@ pub struct Utxo {
 	// The same as `output_index` and `index`.
 	pub vout: u32,
 	pub value: u64,
-	pub atomicals: Vec<()>,
+	pub atomicals: Vec<UtxoAtomical>,
+}
+impl Utxo {
+	/// Whether the UTXO carries no atomical, so it is safe to spend as plain BTC.
+	pub fn is_plain(&self) -> bool {
+		self.atomicals.is_empty()
+	}
+
+	pub fn carries(&self, id: &AtomicalId) -> bool {
+		self.atomicals.iter().any(|a| &a.id == id)
+	}
+
+	/// Classify the UTXO by the types of the atomicals it carries.
+	///
+	/// Return `None` if the type of any of them is unknown.
+	pub fn kind(&self, types: &HashMap<AtomicalId, AtomicalType>) -> Option<UtxoKind> {
+		let (mut ft, mut nft) = (false, false);
+
+		for a in &self.atomicals {
+			match types.get(&a.id)? {
+				AtomicalType::Ft => ft = true,
+				AtomicalType::Nft => nft = true,
+			}
+		}
+
+		Some(match (ft, nft) {
+			(false, false) => UtxoKind::Plain,
+			(true, false) => UtxoKind::Ft,
+			(false, true) => UtxoKind::Nft,
+			(true, true) => UtxoKind::Mixed,
+		})
+	}
 }
 impl From<Unspent> for Utxo {
 	fn from(v: Unspent) -> Self {
 		Self { txid: v.tx_hash, vout: v.tx_pos, value: v.value, atomicals: v.atomicals }
 	}
 }
+impl From<ScripthashUnspent> for Utxo {
+	fn from(v: ScripthashUnspent) -> Self {
+		Self { txid: v.txid, vout: v.vout, value: v.value, atomicals: v.atomicals }
+	}
+}
+#[test]
+fn utxo_should_work() {
+	let id = |i| AtomicalId { txid: Txid::all_zeros(), index: i };
+	let unspent = |atomicals| {
+		serde_json::from_value::<Unspent>(serde_json::json!({
+			"txid": "00", "tx_hash": "00", "index": 0, "tx_pos": 0, "vout": 0, "height": 0,
+			"value": 1000, "atomicals": atomicals
+		}))
+		.unwrap()
+	};
+	let zeros = "0".repeat(64);
+	let u = Utxo::from(unspent(serde_json::json!([format!("{zeros}i0")])));
+
+	assert_eq!(u.atomicals, [UtxoAtomical { id: id(0), value: None }]);
+
+	let u = Utxo::from(unspent(
+		serde_json::json!({ format!("{zeros}i0"): 600, format!("{zeros}i1"): 400 }),
+	));
+
+	assert_eq!(
+		u.atomicals,
+		[
+			UtxoAtomical { id: id(0), value: Some(600) },
+			UtxoAtomical { id: id(1), value: Some(400) }
+		]
+	);
+	assert!(u.carries(&id(1)) && !u.carries(&id(2)) && !u.is_plain());
+
+	let mut types = HashMap::from([(id(0), AtomicalType::Ft)]);
+
+	assert_eq!(u.kind(&types), None);
+
+	types.insert(id(1), AtomicalType::Ft);
+
+	assert_eq!(u.kind(&types), Some(UtxoKind::Ft));
+
+	types.insert(id(1), AtomicalType::Nft);
+
+	assert_eq!(u.kind(&types), Some(UtxoKind::Mixed));
+	assert_eq!(Utxo::from(unspent(serde_json::json!([]))).kind(&types), Some(UtxoKind::Plain));
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UtxoAtomical {
+	pub id: AtomicalId,
+	/// Value carried by the UTXO for the atomical, if the server reports it.
+	pub value: Option<u64>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum AtomicalType {
+	#[serde(rename = "FT")]
+	Ft,
+	#[serde(rename = "NFT")]
+	Nft,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UtxoKind {
+	/// Plain BTC.
+	Plain,
+	/// Carries FTs only.
+	Ft,
+	/// Carries NFTs only.
+	Nft,
+	/// Carries both FTs and NFTs.
+	Mixed,
+}
+
+/// Atomical ID, the location of the reveal input which created the atomical.
+///
+/// Displayed as `TXIDiINDEX`, or in the compact `atomical_ref` form, where the txid is encoded in
+/// Crockford's base32.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AtomicalId {
+	pub txid: Txid,
+	pub index: u32,
+}
+impl AtomicalId {
+	const BASE32: &'static [u8; 32] = b"0123456789abcdefghjkmnpqrstvwxyz";
+
+	/// Encode into the `atomical_ref` form.
+	pub fn to_atomical_ref(&self) -> String {
+		let mut bytes = self.txid.to_byte_array();
+
+		// As displayed.
+		bytes.reverse();
+
+		let mut r = String::with_capacity(52);
+		let (mut acc, mut bits) = (0_u16, 0);
+
+		for b in bytes {
+			acc = (acc << 8) | b as u16;
+			bits += 8;
+
+			while bits >= 5 {
+				bits -= 5;
+				r.push(Self::BASE32[(acc >> bits) as usize & 31] as char);
+			}
+		}
+		if bits > 0 {
+			r.push(Self::BASE32[(acc << (5 - bits)) as usize & 31] as char);
+		}
+
+		format!("{r}i{}", self.index)
+	}
+
+	/// Decode from the `atomical_ref` form.
+	pub fn from_atomical_ref(s: &str) -> Result<Self> {
+		let e = || Error::InvalidAtomicalId(s.into());
+		let (r, index) = s.rsplit_once('i').ok_or_else(e)?;
+		let mut bytes = Vec::with_capacity(32);
+		let (mut acc, mut bits) = (0_u16, 0);
+
+		for c in r.bytes() {
+			let v = match c.to_ascii_lowercase() {
+				b'o' => 0,
+				b'l' => 1,
+				c => Self::BASE32.iter().position(|b| *b == c).ok_or_else(e)? as u16,
+			};
+
+			acc = (acc << 5) | v;
+			bits += 5;
+
+			if bits >= 8 {
+				bits -= 8;
+				bytes.push((acc >> bits) as u8);
+			}
+		}
+
+		let mut bytes = <[u8; 32]>::try_from(bytes).map_err(|_| e())?;
+
+		bytes.reverse();
+
+		Ok(Self { txid: Txid::from_byte_array(bytes), index: index.parse().map_err(|_| e())? })
+	}
+
+	/// Encode into the 36-byte location form, the txid bytes followed by the index in little
+	/// endian.
+	pub fn to_location_bytes(&self) -> [u8; 36] {
+		let mut bytes = [0; 36];
+
+		bytes[..32].copy_from_slice(&self.txid.to_byte_array());
+		bytes[32..].copy_from_slice(&self.index.to_le_bytes());
+
+		bytes
+	}
+}
+impl Display for AtomicalId {
+	fn fmt(&self, f: &mut Formatter) -> FmtResult {
+		write!(f, "{}i{}", self.txid, self.index)
+	}
+}
+impl FromStr for AtomicalId {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self> {
+		let e = || Error::InvalidAtomicalId(s.into());
+		let (txid, index) = s.split_once('i').ok_or_else(e)?;
+
+		Ok(Self { txid: txid.parse().map_err(|_| e())?, index: index.parse().map_err(|_| e())? })
+	}
+}
+impl Serialize for AtomicalId {
+	fn serialize<S>(&self, serializer: S) -> StdResult<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		serializer.collect_str(self)
+	}
+}
+impl<'de> Deserialize<'de> for AtomicalId {
+	fn deserialize<D>(deserializer: D) -> StdResult<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		String::deserialize(deserializer)?.parse().map_err(D::Error::custom)
+	}
+}
+#[test]
+fn atomical_id_should_work() {
+	let s = "9125f3f1c5c2bf3e9b1d3c0a6b6d7c8e2f4a5b6c7d8e9f0a1b2c3d4e5f6a7b8ci0";
+	let id = s.parse::<AtomicalId>().unwrap();
+
+	assert_eq!(id.index, 0);
+	assert_eq!(id.to_string(), s);
+	assert_eq!(serde_json::to_string(&id).unwrap(), format!("\"{s}\""));
+	assert_eq!(serde_json::from_str::<AtomicalId>(&format!("\"{s}\"")).unwrap(), id);
+
+	let r = id.to_atomical_ref();
+
+	assert_eq!(r, "j4jz7we5razkx6rx7g56pvbwhrqmmpvcfp79y2gv5gymwqvafe60i0");
+	assert_eq!(AtomicalId::from_atomical_ref(&r).unwrap(), id);
+	assert_eq!(AtomicalId::from_atomical_ref(&r.to_uppercase().replace('I', "i")).unwrap(), id);
+	assert!("00i0".parse::<AtomicalId>().is_err());
+	assert!(AtomicalId::from_atomical_ref("0ui0").is_err());
+	assert_eq!(
+		"1809bfb8f69a1de200b5a5b2e96afef9f7417ee0772363c7a7cb6591eb1a9b8bi1"
+			.parse::<AtomicalId>()
+			.unwrap()
+			.to_location_bytes()
+			.map(|b| format!("{b:02x}"))
+			.concat(),
+		"8b9b1aeb9165cba7c7632377e07e41f7f9fe6ae9b2a5b500e21d9af6b8bf091801000000"
+	);
+}
@@ -1,22 +1,27 @@
 //! Atomicals electrumx utilities.
 
 // crates.io
-use bitcoin::Address;
+use bitcoin::{Address, Script};
 use sha2::{Digest, Sha256};
 // atomicals-electrumx
 use crate::prelude::*;
 
 /// Convert an address to a scripthash.
 pub fn address2scripthash(address: &Address) -> Result<String> {
+	Ok(script2scripthash(&address.script_pubkey()))
+}
+
+/// Convert a script to a scripthash.
+pub fn script2scripthash(script: &Script) -> String {
 	let mut hasher = Sha256::new();
 
-	hasher.update(address.script_pubkey());
+	hasher.update(script);
 
 	let mut hash = hasher.finalize();
 
 	hash.reverse();
 
-	Ok(array_bytes::bytes2hex("", hash))
+	array_bytes::bytes2hex("", hash)
 }
 #[test]
 fn address2scripthash_should_work() {
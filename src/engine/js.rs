@@ -13,6 +13,7 @@ use std::{
 	time::Duration,
 };
 // crates.io
+use atomicals_electrumx::transport::HttpOptions;
 use tokio::time;
 // atomicalsir
 use crate::{
@@ -27,6 +28,7 @@ pub async fn run(
 	electrumx: &str,
 	atomicals_js_dir: &Path,
 	ticker: &str,
+	http: &HttpOptions,
 ) -> Result<()> {
 	let ws = Wallet::load_wallets(atomicals_js_dir.join("wallets"));
 
@@ -35,7 +37,7 @@ pub async fn run(
 			tracing::info!("");
 			tracing::info!("");
 
-			w.mine(network, fee_bound, electrumx, ticker).await?;
+			w.mine(network, fee_bound, electrumx, ticker, http).await?;
 		}
 	}
 }
@@ -47,12 +49,13 @@ impl Wallet {
 		fee_bound: &FeeBound,
 		electrumx: &str,
 		ticker: &str,
+		http: &HttpOptions,
 	) -> Result<()> {
 		tracing::info!("stash: {}", self.stash.key.address);
 		tracing::info!("funding: {}", self.funding.address);
 
 		let fee = if network == "livenet" {
-			let f = loop_fut(util::query_fee, "fee").await;
+			let f = loop_fut(|| util::query_fee(http), "fee").await;
 
 			tracing::info!("current priority fee: {f} sat/vB");
 
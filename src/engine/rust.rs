@@ -8,6 +8,7 @@ use std::{
 		Arc, Mutex,
 	},
 	thread::{self, JoinHandle},
+	time::Duration,
 };
 // crates.io
 use bitcoin::{
@@ -21,31 +22,49 @@ use bitcoin::{
 	taproot::{LeafVersion, Signature, TapLeafHash, TaprootBuilder, TaprootSpendInfo},
 	transaction::Version,
 	Address, Amount, Network, OutPoint, Psbt, ScriptBuf, Sequence, TapSighashType, Transaction,
-	TxIn, TxOut, Witness,
+	TxIn, TxOut, Txid, Witness,
 };
-use serde::Serialize;
+use tokio::time::{self, Instant};
 // atomicalsir
 use crate::{
 	prelude::*,
 	util::{self, FeeBound},
 	wallet::Wallet as RawWallet,
 };
-use atomicals_electrumx::{r#type::Utxo, Api, ElectrumX, ElectrumXBuilder};
+use atomicals_electrumx::{
+	bitcoind::{Bitcoind, BitcoindBuilder},
+	cache::Cache,
+	chain::{self, Chain},
+	esplora::{Esplora, EsploraBuilder},
+	pool::{Outcome, Pool},
+	prelude::{Error as ApiError, Rejection, Result as ApiResult},
+	r#type::{AtomicalId, Ticker, Utxo},
+	transport::HttpOptions,
+	Api, Client,
+};
 
-pub async fn run(
-	thread: u16,
-	network: Network,
-	fee_bound: &FeeBound,
-	electrumx: &str,
-	wallet_dir: &Path,
-	ticker: &str,
-) -> Result<()> {
-	let m = MinerBuilder { thread, network, fee_bound, electrumx, wallet_dir, ticker }.build()?;
+pub mod container;
+pub mod decode;
+pub mod ft;
+pub mod nft;
+pub mod op;
+pub mod operation;
+use operation::{Dmt, DmtArgs, Operation};
+pub mod realm;
+pub mod transfer;
+
+pub async fn run(builder: MinerBuilder<'_>, wallet_dir: &Path, ticker: &str) -> Result<()> {
+	let network = builder.network;
+	let m = builder.build()?;
+	let ws = RawWallet::load_wallets(wallet_dir)
+		.into_iter()
+		.map(|rw| Wallet::from_raw_wallet(rw, network))
+		.collect::<Result<Vec<_>>>()?;
 
 	#[allow(clippy::never_loop)]
 	loop {
-		for w in &m.wallets {
-			m.mine(w).await?;
+		for w in &ws {
+			m.mine(w, ticker).await?;
 
 			// Test only.
 			// return Ok(());
@@ -58,14 +77,23 @@ struct Miner {
 	thread: u16,
 	network: Network,
 	fee_bound: FeeBound,
-	api: ElectrumX,
-	wallets: Vec<Wallet>,
-	ticker: String,
+	api: Cache<Pool<Client>>,
+	bitcoind: Option<Bitcoind>,
+	esplora: Option<Esplora>,
+	chain_backend: Backend,
+	fee_backend: Backend,
+	http: HttpOptions,
+	atomical_id: Option<AtomicalId>,
+	wait_timeout: Option<Duration>,
 }
 impl Miner {
 	const BASE_BYTES: f64 = 10.5;
+	const DUST: u64 = 546;
 	const INPUT_BYTES_BASE: f64 = 57.5;
 	const LOCK_TIME: LockTime = LockTime::ZERO;
+	// Leave room for the rest of the reveal transaction within the standard weight of `400_000`.
+	const MAX_REVEAL_SCRIPT_BYTES: usize = 390_000;
+	const NFT_VALUE: u64 = 1_000;
 	// Estimated 8-byte value, with a script size of one byte.
 	// The actual size of the value is determined by the final nonce.
 	const OP_RETURN_BYTES: f64 = 21. + 8. + 1.;
@@ -73,120 +101,199 @@ impl Miner {
 	const REVEAL_INPUT_BYTES_BASE: f64 = 66.;
 	const VERSION: Version = Version::ONE;
 
-	async fn mine(&self, wallet: &Wallet) -> Result<()> {
-		let d = self.prepare_data(wallet).await?;
+	async fn mine(&self, wallet: &Wallet, ticker: &str) -> Result<()> {
+		let d = self.prepare_data(wallet, ticker).await?;
+
+		self.execute(wallet, d).await?;
+
+		Ok(())
+	}
 
-		tracing::info!("attempt to find a solution based on {d:#?}");
+	async fn execute(&self, wallet: &Wallet, data: Data) -> Result<Receipt> {
+		tracing::info!("attempt to find a solution based on {data:#?}");
 
 		let Data {
 			secp,
-			satsbyte,
+			mut satsbyte,
 			bitworkc,
 			bitworkr,
+			additional_inputs,
 			additional_outputs,
 			reveal_script,
 			reveal_spend_info,
-			fees,
-			funding_utxo,
-		} = d.clone();
+			mut fees,
+			mut funding_utxo,
+		} = data;
 		let reveal_spk = ScriptBuf::new_p2tr(
 			&secp,
 			reveal_spend_info.internal_key(),
 			reveal_spend_info.merkle_root(),
 		);
 		let funding_spk = wallet.funding.address.script_pubkey();
-		let commit_input = vec![TxIn {
-			previous_output: OutPoint::new(funding_utxo.txid.parse()?, funding_utxo.vout),
-			..Default::default()
-		}];
-		let commit_output = {
-			let spend = TxOut {
-				value: Amount::from_sat(fees.reveal_and_outputs),
-				script_pubkey: reveal_spk.clone(),
-			};
-			let refund = {
-				let r = funding_utxo.value.saturating_sub(fees.reveal_and_outputs).saturating_sub(
-					fees.commit + (Self::OUTPUT_BYTES_BASE * satsbyte as f64).floor() as u64,
-				);
+		// Rebuilt whenever the mempool refuses it for a reason the engine can fix.
+		let (commit_txid, commit_output) = loop {
+			let commit_input = vec![TxIn {
+				previous_output: OutPoint::new(funding_utxo.txid.parse()?, funding_utxo.vout),
+				..Default::default()
+			}];
+			let commit_output = {
+				let spend = TxOut {
+					value: Amount::from_sat(fees.reveal_and_outputs),
+					script_pubkey: reveal_spk.clone(),
+				};
+				let refund = {
+					let r =
+						funding_utxo.value.saturating_sub(fees.reveal_and_outputs).saturating_sub(
+							fees.commit
+								+ (Self::OUTPUT_BYTES_BASE * satsbyte as f64).floor() as u64,
+						);
+
+					if r > 0 {
+						Some(TxOut {
+							value: Amount::from_sat(r),
+							script_pubkey: funding_spk.clone(),
+						})
+					} else {
+						None
+					}
+				};
 
-				if r > 0 {
-					Some(TxOut { value: Amount::from_sat(r), script_pubkey: funding_spk.clone() })
+				if let Some(r) = refund {
+					vec![spend, r]
 				} else {
-					None
+					vec![spend]
 				}
 			};
+			let commit_prevouts = [TxOut {
+				value: Amount::from_sat(funding_utxo.value),
+				script_pubkey: funding_spk.clone(),
+			}];
+			let commit_params = (
+				secp.clone(),
+				wallet.funding.pair.tap_tweak(&secp, None).to_inner(),
+				wallet.funding.x_only_public_key,
+				commit_input.clone(),
+				commit_output.clone(),
+				commit_prevouts.clone(),
+			);
+			let commit_f = |p: &CommitParams, s: u32| {
+				let (secp, signer, signer_xpk, input, output, prevouts) = p;
+				let mut psbt = Psbt::from_unsigned_tx(Transaction {
+					version: Self::VERSION,
+					lock_time: Self::LOCK_TIME,
+					input: {
+						let mut i = input.to_owned();
 
-			if let Some(r) = refund {
-				vec![spend, r]
+						i[0].sequence = Sequence(s);
+
+						i
+					},
+					output: output.to_owned(),
+				})?;
+
+				sign_commit_psbt(secp, signer, signer_xpk, &mut psbt, prevouts)?;
+
+				Ok(psbt.extract_tx_unchecked_fee_rate())
+			};
+			let commit_tx = if let Some(bitworkc) = bitworkc.clone() {
+				WorkerPool::new("commit", bitworkc, self.thread)
+					.activate(commit_params, commit_f)?
+					.result()
 			} else {
-				vec![spend]
+				commit_f(&commit_params, Sequence::ENABLE_RBF_NO_LOCKTIME.0)?
+			};
+			let commit_txid = commit_tx.txid();
+			let commit_tx_hex = encode::serialize_hex(&commit_tx);
+
+			tracing::info!("broadcasting commit transaction {commit_txid}");
+			tracing::debug!("{commit_tx:#?}");
+			tracing::info!("{commit_tx_hex}");
+
+			match self.broadcast(&commit_tx_hex).await {
+				Ok(_) => break (commit_txid, commit_output),
+				Err(e) => match rejection_of(&e) {
+					// Spent by another transaction or dropped from the mempool.
+					Some(Rejection::MissingInputs) => {
+						tracing::warn!("{e:#}; selecting another funding UTXO");
+
+						funding_utxo = self
+							.reselect_funding(
+								wallet,
+								&funding_utxo,
+								fees.commit_and_reveal_and_outputs,
+							)
+							.await?;
+					},
+					// Raise the fee rate as far as `--fee-bound` allows.
+					Some(Rejection::InsufficientFee) if satsbyte < self.fee_bound.max => {
+						let raised = (satsbyte * 2).min(self.fee_bound.max);
+
+						tracing::warn!(
+							"{e:#}; raising the fee rate from {satsbyte} to {raised} sat/vB"
+						);
+
+						satsbyte = raised;
+						fees = Self::fees_of(
+							satsbyte,
+							reveal_script.as_bytes().len(),
+							&additional_inputs
+								.iter()
+								.map(|(_, p)| p.to_owned())
+								.collect::<Vec<_>>(),
+							&additional_outputs,
+							bitworkr.is_some(),
+						);
+
+						if funding_utxo.value < fees.commit_and_reveal_and_outputs {
+							funding_utxo = self
+								.wait_until_utxo(
+									wallet.funding.address.to_string(),
+									fees.commit_and_reveal_and_outputs,
+								)
+								.await?;
+						}
+					},
+					_ => Err(e)?,
+				},
 			}
 		};
-		let commit_prevouts = [TxOut {
-			value: Amount::from_sat(funding_utxo.value),
-			script_pubkey: funding_spk.clone(),
-		}];
-		let commit_tx = WorkerPool::new("commit", bitworkc, self.thread)
-			.activate(
-				(
-					secp.clone(),
-					wallet.funding.pair.tap_tweak(&secp, None).to_inner(),
-					wallet.funding.x_only_public_key,
-					commit_input.clone(),
-					commit_output.clone(),
-					commit_prevouts.clone(),
-				),
-				|(secp, signer, signer_xpk, input, output, prevouts), s| {
-					let mut psbt = Psbt::from_unsigned_tx(Transaction {
-						version: Self::VERSION,
-						lock_time: Self::LOCK_TIME,
-						input: {
-							let mut i = input.to_owned();
-
-							i[0].sequence = Sequence(s);
-
-							i
-						},
-						output: output.to_owned(),
-					})?;
-
-					sign_commit_psbt(secp, signer, signer_xpk, &mut psbt, prevouts)?;
-
-					Ok(psbt.extract_tx_unchecked_fee_rate())
-				},
-			)?
-			.result();
-		let commit_txid = commit_tx.txid();
-		let commit_tx_hex = encode::serialize_hex(&commit_tx);
-
-		tracing::info!("broadcasting commit transaction {commit_txid}");
-		tracing::debug!("{commit_tx:#?}");
-		tracing::info!("{commit_tx_hex}");
-
-		// TODO?: Handle result.
-		self.api.broadcast(commit_tx_hex).await?;
-
-		let commit_txid_ = self
-			.api
-			.wait_until_utxo(
-				Address::from_script(&reveal_spk, self.network)?.to_string(),
-				fees.reveal_and_outputs,
-			)
-			.await?
-			.txid;
+
+		let commit_txid_ = match self.chain_backend {
+			// The wallet of a new address misses the commit if it is already in the mempool, so
+			// look the output up directly.
+			Backend::Bitcoind =>
+				self.bitcoind().wait_for_tx_out(commit_txid.to_string(), 0).await?,
+			_ =>
+				self.wait_until_utxo(
+					Address::from_script(&reveal_spk, self.network)?.to_string(),
+					fees.reveal_and_outputs,
+				)
+				.await?,
+		}
+		.txid;
 
 		assert_eq!(commit_txid, commit_txid_.parse()?);
 
 		let mut reveal_psbt = Psbt::from_unsigned_tx(Transaction {
 			version: Self::VERSION,
 			lock_time: Self::LOCK_TIME,
-			input: vec![TxIn {
-				previous_output: OutPoint::new(commit_txid, 0),
-				sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
-				..Default::default()
-			}],
+			input: [OutPoint::new(commit_txid, 0)]
+				.into_iter()
+				.chain(additional_inputs.iter().map(|(o, _)| *o))
+				.map(|previous_output| TxIn {
+					previous_output,
+					sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+					..Default::default()
+				})
+				.collect(),
 			output: additional_outputs,
 		})?;
+		// The additional inputs are held by the stash.
+		let reveal_prevouts = [commit_output[0].clone()]
+			.into_iter()
+			.chain(additional_inputs.into_iter().map(|(_, p)| p))
+			.collect::<Vec<_>>();
+		let stash_signer = wallet.stash.pair.tap_tweak(&secp, None).to_inner();
 		let reveal_lh = reveal_script.tapscript_leaf_hash();
 		let reveal_tx = if let Some(bitworkr) = bitworkr {
 			let time = util::time();
@@ -199,10 +306,11 @@ impl Miner {
 						wallet.funding.pair,
 						reveal_script.clone(),
 						reveal_spend_info.clone(),
-						commit_output[0].clone(),
+						reveal_prevouts.clone(),
 						reveal_psbt.clone(),
+						(stash_signer, wallet.stash.x_only_public_key),
 					),
-					move |(secp, signer, script, spend_info, output, psbt), s| {
+					move |(secp, signer, script, spend_info, prevouts, psbt, stash), s| {
 						let mut psbt = psbt.to_owned();
 
 						psbt.unsigned_tx.output.push(TxOut {
@@ -212,9 +320,19 @@ impl Miner {
 						psbt.outputs.push(Default::default());
 
 						sign_reveal_psbt(
-							secp, signer, &mut psbt, output, &reveal_lh, spend_info, script,
+							secp,
+							signer,
+							&mut psbt,
+							&prevouts[0],
+							&reveal_lh,
+							spend_info,
+							script,
 						)?;
 
+						for i in 1..prevouts.len() {
+							sign_key_spend_psbt(secp, &stash.0, &stash.1, &mut psbt, prevouts, i)?;
+						}
+
 						Ok(psbt.extract_tx_unchecked_fee_rate())
 					},
 				)?
@@ -230,6 +348,17 @@ impl Miner {
 				&reveal_script,
 			)?;
 
+			for i in 1..reveal_prevouts.len() {
+				sign_key_spend_psbt(
+					&secp,
+					&stash_signer,
+					&wallet.stash.x_only_public_key,
+					&mut reveal_psbt,
+					&reveal_prevouts,
+					i,
+				)?;
+			}
+
 			// Remove this clone if not needed in the future.
 			reveal_psbt.clone().extract_tx_unchecked_fee_rate()
 		};
@@ -240,27 +369,49 @@ impl Miner {
 		tracing::debug!("{reveal_tx:#?}");
 		tracing::info!("{reveal_tx_hex}");
 
-		if let Err(e) = self.api.broadcast(&reveal_tx_hex).await {
-			tracing::error!("failed to broadcast reveal transaction due to {e}");
+		let revealed = if let Err(e) = self.broadcast(&reveal_tx_hex).await {
+			tracing::error!("failed to broadcast reveal transaction due to {e:#}");
 
 			util::cache(
 				reveal_txid.to_string(),
 				format!("{reveal_tx_hex}\n{reveal_psbt:?}\n{reveal_tx:?}"),
 			)?;
-		}
 
-		Ok(())
+			false
+		} else {
+			true
+		};
+
+		Ok(Receipt { commit_txid, reveal_txid, revealed })
 	}
 
-	async fn prepare_data(&self, wallet: &Wallet) -> Result<Data> {
-		let id = self.api.get_by_ticker(&self.ticker).await?.atomical_id;
-		let response = self.api.get_ft_info(id).await?;
+	async fn prepare_data(&self, wallet: &Wallet, ticker: &str) -> Result<Data> {
+		let id = resolve_ticker(
+			ticker,
+			&self.api.get_by_ticker(ticker).await?,
+			self.atomical_id.as_ref(),
+		)?;
+		let response = self.api.get_ft_info(id.to_string()).await?;
 		let global = response.global.unwrap();
 		let ft = response.result;
 
-		if ft.ticker != self.ticker {
+		if ft.ticker != ticker {
 			Err(anyhow::anyhow!("ticker mismatch"))?;
 		}
+		if ft.atomical_id != id.to_string() {
+			Err(anyhow::anyhow!("atomical ID mismatch"))?;
+		}
+		// A pinned candidate is minted whatever its status.
+		if self.atomical_id.is_none()
+			&& (ft.request_ticker_status.status != "verified"
+				|| ft.request_ticker_status.verified_atomical_id != ft.atomical_id)
+		{
+			Err(anyhow::anyhow!(
+				"ticker {ticker} is {} for {}",
+				ft.request_ticker_status.status,
+				ft.atomical_id
+			))?;
+		}
 		if ft.subtype != "decentralized" {
 			Err(anyhow::anyhow!("not decentralized"))?;
 		}
@@ -274,32 +425,56 @@ impl Miner {
 			Err(anyhow::anyhow!("max mints reached"))?;
 		}
 
-		let secp = Secp256k1::new();
-		let satsbyte = if self.network == Network::Bitcoin {
-			self.fee_bound.apply(util::query_fee().await? + 5)
-		} else {
-			2
-		};
 		let additional_outputs = vec![TxOut {
 			value: Amount::from_sat(ft.mint_amount),
 			script_pubkey: wallet.stash.address.script_pubkey(),
 		}];
-		let payload = PayloadWrapper {
+		let op = Operation::Dmt(Dmt {
 			args: {
 				let (time, nonce) = util::time_nonce();
 
-				Payload {
-					bitworkc: ft.mint_bitworkc.clone(),
+				DmtArgs {
+					bitworkc: Some(ft.mint_bitworkc.clone()),
+					bitworkr: ft.mint_bitworkr.clone(),
 					mint_ticker: ft.ticker.clone(),
 					nonce,
 					time,
 				}
 			},
-		};
-		let payload_encoded = util::cbor(&payload)?;
-		// TODO: More op types.
-		let reveal_script =
-			util::build_reval_script(&wallet.funding.x_only_public_key, "dmt", &payload_encoded);
+		});
+
+		self.build_data(
+			wallet,
+			&op,
+			Vec::new(),
+			additional_outputs,
+			Some(ft.mint_bitworkc),
+			ft.mint_bitworkr,
+		)
+		.await
+	}
+
+	async fn build_data(
+		&self,
+		wallet: &Wallet,
+		op: &Operation,
+		additional_inputs: Vec<(OutPoint, TxOut)>,
+		additional_outputs: Vec<TxOut>,
+		bitworkc: Option<String>,
+		bitworkr: Option<String>,
+	) -> Result<Data> {
+		let secp = Secp256k1::new();
+		let satsbyte = self.satsbyte().await?;
+		let reveal_script = op.reveal_script(&wallet.funding.x_only_public_key)?;
+
+		if reveal_script.len() > Self::MAX_REVEAL_SCRIPT_BYTES {
+			Err(anyhow::anyhow!(
+				"reveal script of {} bytes exceeds the maximum of {} bytes",
+				reveal_script.len(),
+				Self::MAX_REVEAL_SCRIPT_BYTES
+			))?;
+		}
+
 		let reveal_spend_info = TaprootBuilder::new()
 			.add_leaf(0, reveal_script.clone())?
 			.finalize(&secp, wallet.funding.x_only_public_key)
@@ -307,19 +482,20 @@ impl Miner {
 		let fees = Self::fees_of(
 			satsbyte,
 			reveal_script.as_bytes().len(),
+			&additional_inputs.iter().map(|(_, p)| p.to_owned()).collect::<Vec<_>>(),
 			&additional_outputs,
-			ft.mint_bitworkr.is_some(),
+			bitworkr.is_some(),
 		);
 		let funding_utxo = self
-			.api
 			.wait_until_utxo(wallet.funding.address.to_string(), fees.commit_and_reveal_and_outputs)
 			.await?;
 
 		Ok(Data {
 			secp,
 			satsbyte,
-			bitworkc: ft.mint_bitworkc,
-			bitworkr: ft.mint_bitworkr,
+			bitworkc,
+			bitworkr,
+			additional_inputs,
 			additional_outputs,
 			reveal_script,
 			reveal_spend_info,
@@ -328,9 +504,187 @@ impl Miner {
 		})
 	}
 
+	// The builder makes sure the selected backends are configured.
+	fn bitcoind(&self) -> &Bitcoind {
+		self.bitcoind.as_ref().expect("checked by the builder")
+	}
+
+	fn esplora(&self) -> &Esplora {
+		self.esplora.as_ref().expect("checked by the builder")
+	}
+
+	async fn wait_until_utxo(&self, address: String, satoshis: u64) -> ApiResult<Utxo> {
+		match self.chain_backend {
+			Backend::Electrumx => self.api.wait_until_utxo(address, satoshis).await,
+			// Neither can tell which UTXOs carry atomicals, so check them against the electrumx.
+			Backend::Bitcoind =>
+				chain::wait_for_plain_utxo(
+					self.bitcoind(),
+					&self.api,
+					&address,
+					satoshis,
+					self.wait_timeout,
+				)
+				.await,
+			Backend::Esplora =>
+				chain::wait_for_plain_utxo(
+					self.esplora(),
+					&self.api,
+					&address,
+					satoshis,
+					self.wait_timeout,
+				)
+				.await,
+		}
+	}
+
+	// Select another funding UTXO once the current one turned out to be spent.
+	async fn reselect_funding(&self, wallet: &Wallet, spent: &Utxo, satoshis: u64) -> Result<Utxo> {
+		let since = Instant::now();
+
+		loop {
+			let u = self.wait_until_utxo(wallet.funding.address.to_string(), satoshis).await?;
+
+			// The index might not have seen the spend yet.
+			if (&u.txid, u.vout) != (&spent.txid, spent.vout) {
+				return Ok(u);
+			}
+
+			self.poll_later(since, Duration::from_secs(5), "another funding UTXO").await?;
+		}
+	}
+
+	// Sleep until the next poll, or give up if it would come after the wait timeout.
+	async fn poll_later(&self, since: Instant, period: Duration, what: &str) -> Result<()> {
+		if let Some(t) = self.wait_timeout {
+			if since.elapsed() + period > t {
+				Err(anyhow::anyhow!("timed out after {t:?} awaiting {what}"))?;
+			}
+		}
+
+		time::sleep(period).await;
+
+		Ok(())
+	}
+
+	// The rejection is kept in the error for the callers to act on, see `rejection_of`.
+	async fn broadcast(&self, tx_hex: &str) -> Result<Txid> {
+		let since = Instant::now();
+
+		loop {
+			// Let Bitcoin Core vet the transaction before it goes anywhere.
+			let r = match self.chain_backend {
+				Backend::Bitcoind => match self.bitcoind().test_mempool_accept(tx_hex).await {
+					Ok(()) => self.broadcast_everywhere(tx_hex).await,
+					Err(e) => Err(e),
+				},
+				_ => self.broadcast_everywhere(tx_hex).await,
+			};
+			let (reason, message) = match r {
+				Ok(txid) => return Ok(txid),
+				Err(ApiError::Rejected { reason, message }) => (reason, message),
+				Err(e) => Err(e)?,
+			};
+
+			let hint = match reason {
+				// The ancestors confirm eventually, the transaction is still good after that.
+				Rejection::TooLongMempoolChain => {
+					tracing::warn!("{message}; retrying in 1 minute");
+
+					self.poll_later(
+						since,
+						Duration::from_secs(60),
+						"the unconfirmed ancestors to confirm",
+					)
+					.await?;
+
+					continue;
+				},
+				Rejection::InsufficientFee =>
+					"the fee rate is too low for the mempool, consider raising `--fee-bound`",
+				Rejection::MissingInputs =>
+					"the inputs are missing or were spent by another transaction",
+				Rejection::Dust => "an output is below the dust limit",
+				Rejection::NonStandard => "the transaction is non-standard",
+			};
+
+			Err(Error::new(ApiError::Rejected { reason, message }).context(hint))?;
+		}
+	}
+
+	// Push the transaction to every backend at once, any of them accepting it is enough.
+	async fn broadcast_everywhere(&self, tx_hex: &str) -> ApiResult<Txid> {
+		let (mut os, b, e) = tokio::join!(
+			self.api.inner.broadcast_all(tx_hex),
+			async {
+				match &self.bitcoind {
+					Some(b) => Some(Outcome {
+						backend: b.name(),
+						result: b.send_raw_transaction(tx_hex).await,
+					}),
+					None => None,
+				}
+			},
+			async {
+				match &self.esplora {
+					Some(e) =>
+						Some(Outcome { backend: e.uri.clone(), result: e.broadcast(tx_hex).await }),
+					None => None,
+				}
+			}
+		);
+
+		os.extend(b.into_iter().chain(e));
+
+		for o in &os {
+			match &o.result {
+				Ok(txid) => tracing::info!("{} accepted transaction {txid}", o.backend),
+				Err(e) => tracing::warn!("{} refused the transaction due to {e}", o.backend),
+			}
+		}
+
+		let mut es = Vec::new();
+
+		for o in os {
+			match o.result {
+				Ok(txid) => return Ok(txid),
+				Err(e) => es.push(e),
+			}
+		}
+
+		// A rejection tells more about the transaction than a failed request.
+		let i = es.iter().position(|e| matches!(e, ApiError::Rejected { .. })).unwrap_or(0);
+
+		Err(es.swap_remove(i))
+	}
+
+	async fn satsbyte(&self) -> Result<u64> {
+		Ok(if self.network == Network::Bitcoin {
+			let fee = match self.fee_backend {
+				Backend::Electrumx => self.api.fee_rate(1).await?,
+				Backend::Bitcoind => self.bitcoind().fee_rate(1).await?,
+				// mempool.space unless another instance is given.
+				Backend::Esplora => match &self.esplora {
+					Some(e) => e.fee_rate(1).await?,
+					None =>
+						EsploraBuilder::default()
+							.http(self.http.clone())
+							.build()?
+							.fee_rate(1)
+							.await?,
+				},
+			};
+
+			self.fee_bound.apply(fee + 5)
+		} else {
+			2
+		})
+	}
+
 	fn fees_of(
 		satsbyte: u64,
 		reveal_script_len: usize,
+		additional_inputs: &[TxOut],
 		additional_outputs: &[TxOut],
 		has_bitworkr: bool,
 	) -> Fees {
@@ -353,14 +707,19 @@ impl Miner {
 
 			(satsbyte
 				* (Self::BASE_BYTES
-						+ Self::REVEAL_INPUT_BYTES_BASE
-						+ (compact_input_bytes + reveal_script_len as f64) / 4.
-						// + utxos.len() as f64 * Self::INPUT_BYTES_BASE
-						+ additional_outputs.len() as f64 * Self::OUTPUT_BYTES_BASE
-						+ op_return_bytes))
+					+ Self::REVEAL_INPUT_BYTES_BASE
+					+ (compact_input_bytes + reveal_script_len as f64) / 4.
+					+ additional_inputs.len() as f64 * Self::INPUT_BYTES_BASE
+					+ additional_outputs.len() as f64 * Self::OUTPUT_BYTES_BASE
+					+ op_return_bytes))
 				.ceil() as u64
 		};
-		let outputs = additional_outputs.iter().map(|o| o.value.to_sat()).sum::<u64>();
+		// The additional inputs carry their own value into the outputs.
+		let outputs = additional_outputs
+			.iter()
+			.map(|o| o.value.to_sat())
+			.sum::<u64>()
+			.saturating_sub(additional_inputs.iter().map(|i| i.value.to_sat()).sum::<u64>());
 		let commit_and_reveal = commit + reveal;
 		let commit_and_reveal_and_outputs = commit_and_reveal + outputs;
 
@@ -381,41 +740,435 @@ impl Miner {
 		}
 	}
 }
+// The mempool rejection behind the error, if any.
+fn rejection_of(e: &Error) -> Option<Rejection> {
+	match e.downcast_ref::<ApiError>() {
+		Some(ApiError::Rejected { reason, .. }) => Some(*reason),
+		_ => None,
+	}
+}
+
+#[test]
+fn fees_of_should_work() {
+	let outputs =
+		[TxOut { value: Amount::from_sat(1_000), script_pubkey: ScriptBuf::new_op_return([]) }];
+
+	assert_eq!(Miner::fees_of(2, 200, &[], &outputs, false).reveal_and_outputs, 1_000 + 340);
+	// The witness script length is encoded in 3 bytes.
+	assert_eq!(Miner::fees_of(2, 60_000, &[], &outputs, false).reveal_and_outputs, 1_000 + 30_241);
+	// The witness script length is encoded in 5 bytes.
+	assert_eq!(Miner::fees_of(2, 100_000, &[], &outputs, false).reveal_and_outputs, 1_000 + 50_242);
+	// The additional input carries the value of the output forward.
+	assert_eq!(Miner::fees_of(2, 200, &outputs, &outputs, false).reveal_and_outputs, 340 + 115);
+}
+#[test]
+fn mine_should_work() {
+	// crates.io
+	use bitcoin::secp256k1::rand;
+	use tokio::runtime::Runtime;
+	// atomicalsir
+	use atomicals_electrumx::mock::{Ft, Mock};
+
+	let network = Network::Testnet;
+	let secp = Secp256k1::new();
+	let key = || {
+		let pair = Keypair::new(&secp, &mut rand::thread_rng());
+		let x_only_public_key = pair.x_only_public_key().0;
+
+		Key {
+			pair,
+			x_only_public_key,
+			address: Address::p2tr(&secp, x_only_public_key, None, network),
+		}
+	};
+	let wallet = Wallet { stash: key(), funding: key() };
+
+	Runtime::new().unwrap().block_on(async {
+		let m = Mock::start().await;
+		let electrumx = [m.uri.clone()];
+		let miner = MinerBuilder {
+			thread: 2,
+			network,
+			fee_bound: &FeeBound { min: 1, max: 100 },
+			electrumx: &electrumx,
+			wait_timeout: Some(Duration::from_secs(10)),
+			bitcoind: None,
+			bitcoind_cookie: None,
+			esplora: None,
+			chain_backend: Backend::Electrumx,
+			fee_backend: Backend::Esplora,
+			http: HttpOptions::default(),
+			atomical_id: None,
+		}
+		.build()
+		.unwrap();
+
+		m.state().add_ft(Ft {
+			mint_bitworkc: "ab".into(),
+			mint_bitworkr: Some("c".into()),
+			..Ft::easy("atomicalsir")
+		});
+		m.state().fund(&wallet.funding.address, 100_000);
+
+		// A rejected commit leaves everything as it was.
+		m.state().reject_next("dust");
+
+		let e = miner.mine(&wallet, "atomicalsir").await.unwrap_err();
+
+		assert!(e.to_string().contains("below the dust limit"));
+		assert!(m.state().broadcasts.is_empty());
+
+		miner.mine(&wallet, "atomicalsir").await.unwrap();
+
+		let s = m.state();
+		let [commit, reveal] = &s.broadcasts[..] else { panic!("expected a commit and a reveal") };
+
+		assert!(commit.txid().to_string().starts_with("ab"));
+		assert!(reveal.txid().to_string().starts_with('c'));
+		assert_eq!(reveal.input[0].previous_output, OutPoint::new(commit.txid(), 0));
+		assert_eq!(
+			reveal.output[0],
+			TxOut {
+				value: Amount::from_sat(1_000),
+				script_pubkey: wallet.stash.address.script_pubkey()
+			}
+		);
+		assert_eq!(s.utxos_of(&wallet.stash.address).len(), 1);
+		// The funding UTXO is spent, the refund of the commit is left.
+		assert_eq!(s.utxos_of(&wallet.funding.address)[0].txid, commit.txid().to_string());
+	});
+
+	// Nothing to mint.
+	Runtime::new().unwrap().block_on(async {
+		let m = Mock::start().await;
+		let electrumx = [m.uri.clone()];
+		let mut miner = MinerBuilder {
+			thread: 1,
+			network,
+			fee_bound: &FeeBound { min: 1, max: 100 },
+			electrumx: &electrumx,
+			wait_timeout: Some(Duration::from_millis(10)),
+			bitcoind: None,
+			bitcoind_cookie: None,
+			esplora: None,
+			chain_backend: Backend::Electrumx,
+			fee_backend: Backend::Esplora,
+			http: HttpOptions::default(),
+			atomical_id: None,
+		}
+		.build()
+		.unwrap();
+
+		assert!(miner.mine(&wallet, "atomicalsir").await.is_err());
+
+		miner.api.height_interval = Duration::ZERO;
+
+		m.state().add_ft(Ft { mint_count: 21_000, ..Ft::easy("atomicalsir") });
+		// The mint count is cached until the next block.
+		m.state().height += 1;
+
+		let e = miner.mine(&wallet, "atomicalsir").await.unwrap_err();
+
+		assert_eq!(e.to_string(), "max mints reached");
+	});
+}
+#[test]
+fn broadcast_rejections_should_work() {
+	// crates.io
+	use bitcoin::secp256k1::rand;
+	use tokio::runtime::Runtime;
+	// atomicalsir
+	use atomicals_electrumx::mock::{Ft, Mock};
+
+	let network = Network::Testnet;
+	let secp = Secp256k1::new();
+	let key = || {
+		let pair = Keypair::new(&secp, &mut rand::thread_rng());
+		let x_only_public_key = pair.x_only_public_key().0;
+
+		Key {
+			pair,
+			x_only_public_key,
+			address: Address::p2tr(&secp, x_only_public_key, None, network),
+		}
+	};
+	let wallet = Wallet { stash: key(), funding: key() };
+
+	Runtime::new().unwrap().block_on(async {
+		let m = Mock::start().await;
+		let electrumx = [m.uri.clone()];
+		// Testnet mints at 2 sat/vB, so there is room for a single raise.
+		let miner = MinerBuilder {
+			thread: 2,
+			network,
+			fee_bound: &FeeBound { min: 1, max: 4 },
+			electrumx: &electrumx,
+			wait_timeout: Some(Duration::from_secs(10)),
+			bitcoind: None,
+			bitcoind_cookie: None,
+			esplora: None,
+			chain_backend: Backend::Electrumx,
+			fee_backend: Backend::Esplora,
+			http: HttpOptions::default(),
+			atomical_id: None,
+		}
+		.build()
+		.unwrap();
+		let mine_rejected = |message: &'static str| {
+			m.state().reject_next(message);
+
+			async { miner.mine(&wallet, "atomicalsir").await }
+		};
+
+		m.state().add_ft(Ft::easy("atomicalsir"));
+		m.state().fund(&wallet.funding.address, 100_000);
+
+		// Nothing the engine can fix, give up at once.
+		let e = mine_rejected("dust").await.unwrap_err();
+
+		assert!(e.to_string().contains("below the dust limit"));
+		assert_eq!(rejection_of(&e), Some(Rejection::Dust));
+
+		let e = mine_rejected("scriptpubkey").await.unwrap_err();
+
+		assert!(e.to_string().contains("non-standard"));
+		assert_eq!(rejection_of(&e), Some(Rejection::NonStandard));
+
+		// Waiting for the ancestors to confirm would outlast the wait timeout.
+		let e = mine_rejected("too-long-mempool-chain").await.unwrap_err();
+
+		assert!(e.to_string().contains("timed out after 10s"));
+		assert!(m.state().broadcasts.is_empty());
+		assert!(m.state().rejections.is_empty());
+
+		// Rebuilt at a higher fee rate.
+		mine_rejected("min relay fee not met").await.unwrap();
+
+		{
+			let mut s = m.state();
+			let [commit, reveal] = &s.broadcasts[..] else {
+				panic!("expected a commit and a reveal")
+			};
+			let fee = commit.output[0].value.to_sat() - reveal.output[0].value.to_sat();
+
+			assert!(fee as f64 / reveal.vsize() as f64 > 3.);
+
+			s.broadcasts.clear();
+		}
+
+		// Not beyond `--fee-bound`.
+		m.state().reject_next("min relay fee not met");
+
+		let e = mine_rejected("min relay fee not met").await.unwrap_err();
+
+		assert!(e.to_string().contains("consider raising `--fee-bound`"));
+		assert!(m.state().broadcasts.is_empty());
+
+		// Another funding UTXO is selected once the current one is spent elsewhere.
+		let d = miner.prepare_data(&wallet, "atomicalsir").await.unwrap();
+		let spent = d.funding_utxo.clone();
+
+		m.state().utxos.clear();
+
+		let funding = m.state().fund(&wallet.funding.address, 100_000);
+
+		m.state().reject_next("bad-txns-inputs-missingorspent");
+
+		assert!(miner.execute(&wallet, d).await.unwrap().revealed);
+
+		let s = m.state();
+
+		assert_ne!(spent.txid, funding.txid);
+		assert_eq!(
+			s.broadcasts[0].input[0].previous_output,
+			OutPoint::new(funding.txid.parse().unwrap(), funding.vout)
+		);
+	});
+}
+// Only a verified ticker is used, unless one of its candidates is pinned.
+fn resolve_ticker(ticker: &str, t: &Ticker, pinned: Option<&AtomicalId>) -> Result<AtomicalId> {
+	let candidates = t
+		.candidates
+		.iter()
+		.map(|c| c.atomical_id.parse())
+		.collect::<ApiResult<Vec<AtomicalId>>>()?;
+	let report = || {
+		candidates
+			.iter()
+			.zip(&t.candidates)
+			.map(|(id, c)| format!("{id} (commit height {})", c.commit_height))
+			.collect::<Vec<_>>()
+			.join(", ")
+	};
+
+	if t.status == "verified" {
+		let id = t
+			.atomical_id
+			.as_deref()
+			.ok_or_else(|| anyhow::anyhow!("ticker {ticker} is verified without an atomical ID"))?
+			.parse()?;
+
+		match pinned {
+			Some(p) if *p != id =>
+				Err(anyhow::anyhow!("ticker {ticker} is verified as {id}, not the pinned {p}"))?,
+			_ => Ok(id),
+		}
+	} else if let Some(p) = pinned {
+		if !candidates.contains(p)
+			&& t.candidate_atomical_id.as_deref() != Some(p.to_string().as_str())
+		{
+			Err(anyhow::anyhow!(
+				"pinned {p} is not a candidate of ticker {ticker}, the candidates are [{}]",
+				report()
+			))?;
+		}
+
+		tracing::warn!("using the pinned candidate {p} of the {} ticker {ticker}", t.status);
+
+		Ok(*p)
+	} else if t.candidates.is_empty() {
+		Err(anyhow::anyhow!("ticker {ticker} is {}", t.status))?
+	} else {
+		Err(anyhow::anyhow!(
+			"ticker {ticker} is {} with candidates [{}], pin one with `--atomical-id` to use it",
+			t.status,
+			report()
+		))?
+	}
+}
+#[test]
+fn resolve_ticker_should_work() {
+	// atomicals-electrumx
+	use atomicals_electrumx::r#type::Candidate;
+
+	let a = format!("{}i0", "aa".repeat(32));
+	let b = format!("{}i0", "bb".repeat(32));
+	let c = format!("{}i0", "cc".repeat(32));
+	let candidate = |id: &str, commit_height| Candidate {
+		tx_num: 0,
+		atomical_id: id.into(),
+		commit_height,
+		reveal_location_height: commit_height,
+	};
+	let ticker = |status: &str, atomical_id: Option<&str>| Ticker {
+		status: status.into(),
+		candidate_atomical_id: Some(a.clone()),
+		atomical_id: atomical_id.map(Into::into),
+		candidates: vec![candidate(&a, 1), candidate(&b, 2)],
+		r#type: "ticker".into(),
+	};
+	let id = |s: &str| s.parse::<AtomicalId>().unwrap();
+
+	let verified = ticker("verified", Some(&a));
+
+	assert_eq!(resolve_ticker("sir", &verified, None).unwrap(), id(&a));
+	assert_eq!(resolve_ticker("sir", &verified, Some(&id(&a))).unwrap(), id(&a));
+	assert!(resolve_ticker("sir", &verified, Some(&id(&b))).is_err());
+
+	let pending = ticker("pending", None);
+	let e = resolve_ticker("sir", &pending, None).unwrap_err().to_string();
+
+	assert!(e.contains(&format!("{a} (commit height 1), {b} (commit height 2)")));
+	assert_eq!(resolve_ticker("sir", &pending, Some(&id(&b))).unwrap(), id(&b));
+	assert!(resolve_ticker("sir", &pending, Some(&id(&c))).is_err());
+
+	let not_found =
+		Ticker { candidates: Vec::new(), candidate_atomical_id: None, ..ticker("not_found", None) };
+
+	assert!(resolve_ticker("sir", &not_found, None).is_err());
+}
 #[derive(Debug)]
-struct MinerBuilder<'a> {
-	thread: u16,
-	network: Network,
-	fee_bound: &'a FeeBound,
-	electrumx: &'a str,
-	wallet_dir: &'a Path,
-	ticker: &'a str,
+pub struct MinerBuilder<'a> {
+	pub thread: u16,
+	pub network: Network,
+	pub fee_bound: &'a FeeBound,
+	pub electrumx: &'a [String],
+	pub wait_timeout: Option<Duration>,
+	pub bitcoind: Option<&'a str>,
+	pub bitcoind_cookie: Option<&'a Path>,
+	pub esplora: Option<&'a str>,
+	pub chain_backend: Backend,
+	pub fee_backend: Backend,
+	pub http: HttpOptions,
+	pub atomical_id: Option<AtomicalId>,
 }
 impl<'a> MinerBuilder<'a> {
 	fn build(self) -> Result<Miner> {
-		let api =
-			ElectrumXBuilder::default().network(self.network).base_uri(self.electrumx).build()?;
-		let wallets = RawWallet::load_wallets(self.wallet_dir)
-			.into_iter()
-			.map(|rw| Wallet::from_raw_wallet(rw, self.network))
-			.collect::<Result<_>>()?;
+		let mut api = Pool::from_uris_with_http(self.network, self.electrumx, &self.http)?;
+
+		api.wait_timeout = self.wait_timeout;
+
+		let bitcoind = self
+			.bitcoind
+			.map(|u| {
+				let b = BitcoindBuilder::default().uri(u).wait_timeout(self.wait_timeout);
+
+				match self.bitcoind_cookie {
+					Some(c) => b.cookie(c),
+					None => b,
+				}
+				.build()
+			})
+			.transpose()?;
+
+		let esplora = self
+			.esplora
+			.map(|u| {
+				EsploraBuilder::default()
+					.uri(u)
+					.wait_timeout(self.wait_timeout)
+					.http(self.http.clone())
+					.build()
+			})
+			.transpose()?;
+
+		if self.chain_backend == Backend::Bitcoind && bitcoind.is_none() {
+			Err(anyhow::anyhow!("`--chain-backend bitcoind` requires `--bitcoind`"))?;
+		}
+		if self.chain_backend == Backend::Esplora && esplora.is_none() {
+			Err(anyhow::anyhow!("`--chain-backend esplora` requires `--esplora`"))?;
+		}
+		if self.fee_backend == Backend::Bitcoind && bitcoind.is_none() {
+			Err(anyhow::anyhow!("`--fee-backend bitcoind` requires `--bitcoind`"))?;
+		}
 
 		Ok(Miner {
 			thread: self.thread,
 			network: self.network,
 			fee_bound: self.fee_bound.to_owned(),
-			api,
-			wallets,
-			ticker: self.ticker.into(),
+			api: Cache::new(api),
+			bitcoind,
+			esplora,
+			chain_backend: self.chain_backend,
+			fee_backend: self.fee_backend,
+			http: self.http,
+			atomical_id: self.atomical_id,
+			wait_timeout: self.wait_timeout,
 		})
 	}
 }
 
+/// Backend for the chain-facing queries, the Atomicals ones always go to the electrumx.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+	/// The electrumx.
+	Electrumx,
+	/// The Bitcoin Core node from `--bitcoind`.
+	Bitcoind,
+	/// The Esplora instance from `--esplora`.
+	Esplora,
+}
+
 #[derive(Clone, Debug)]
 struct Wallet {
 	stash: Key,
 	funding: Key,
 }
 impl Wallet {
+	fn load(path: &Path, network: Network) -> Result<Self> {
+		Self::from_raw_wallet(RawWallet::load(path)?, network)
+	}
+
 	fn from_raw_wallet(raw_wallet: RawWallet, network: Network) -> Result<Self> {
 		let s_p = util::keypair_from_wif(&raw_wallet.stash.key.wif)?;
 		let f_p = util::keypair_from_wif(&raw_wallet.funding.wif)?;
@@ -444,32 +1197,33 @@ struct Key {
 	address: Address,
 }
 
-#[derive(Debug, Serialize)]
-pub struct PayloadWrapper {
-	pub args: Payload,
-}
-#[derive(Debug, Serialize)]
-pub struct Payload {
-	pub bitworkc: String,
-	// TODO: This field is unnecessary in the current version.
-	// pub bitworkr: Option<String>,
-	pub mint_ticker: String,
-	pub nonce: u64,
-	pub time: u64,
-}
-
 #[derive(Clone, Debug)]
 struct Data {
 	secp: Secp256k1<All>,
 	satsbyte: u64,
-	bitworkc: String,
+	bitworkc: Option<String>,
 	bitworkr: Option<String>,
+	additional_inputs: Vec<(OutPoint, TxOut)>,
 	additional_outputs: Vec<TxOut>,
 	reveal_script: ScriptBuf,
 	reveal_spend_info: TaprootSpendInfo,
 	fees: Fees,
 	funding_utxo: Utxo,
 }
+type CommitParams = (Secp256k1<All>, Keypair, XOnlyPublicKey, Vec<TxIn>, Vec<TxOut>, [TxOut; 1]);
+
+#[derive(Debug)]
+struct Receipt {
+	commit_txid: Txid,
+	reveal_txid: Txid,
+	revealed: bool,
+}
+impl Receipt {
+	fn atomical_id(&self) -> String {
+		format!("{}i0", self.commit_txid)
+	}
+}
+
 #[derive(Clone, Debug)]
 struct Fees {
 	commit: u64,
@@ -565,11 +1319,22 @@ fn sign_commit_psbt(
 	signer_xpk: &XOnlyPublicKey,
 	psbt: &mut Psbt,
 	prevouts: &[TxOut],
+) -> Result<()> {
+	sign_key_spend_psbt(secp, signer, signer_xpk, psbt, prevouts, 0)
+}
+
+fn sign_key_spend_psbt(
+	secp: &Secp256k1<All>,
+	signer: &Keypair,
+	signer_xpk: &XOnlyPublicKey,
+	psbt: &mut Psbt,
+	prevouts: &[TxOut],
+	input: usize,
 ) -> Result<()> {
 	let commit_hty = TapSighashType::Default;
 	let tap_key_sig = {
 		let h = SighashCache::new(&psbt.unsigned_tx).taproot_key_spend_signature_hash(
-			0,
+			input,
 			&Prevouts::All(prevouts),
 			commit_hty,
 		)?;
@@ -578,8 +1343,8 @@ fn sign_commit_psbt(
 		Signature { sig: secp.sign_schnorr(&m, signer), hash_ty: commit_hty }
 	};
 
-	psbt.inputs[0] = Input {
-		witness_utxo: Some(prevouts[0].clone()),
+	psbt.inputs[input] = Input {
+		witness_utxo: Some(prevouts[input].clone()),
 		final_script_witness: {
 			let mut w = Witness::new();
 
@@ -0,0 +1,180 @@
+// std
+use std::{fs, path::Path};
+// crates.io
+use bitcoin::hashes::{sha256, Hash};
+// atomicalsir
+use super::{
+	operation::{File, Nft, NftArgs, ProofItem},
+	realm, Miner, MinerBuilder, Wallet,
+};
+use crate::{prelude::*, util};
+use atomicals_electrumx::Api;
+
+pub async fn claim_container(
+	builder: MinerBuilder<'_>,
+	wallet: &Path,
+	container: &str,
+	bitworkc: Option<String>,
+	bitworkr: Option<String>,
+) -> Result<()> {
+	let w = Wallet::load(wallet, builder.network)?;
+	let m = builder.build()?;
+
+	m.claim_container(&w, container, bitworkc, bitworkr).await
+}
+
+pub async fn mint_item(builder: MinerBuilder<'_>, wallet: &Path, item: Item<'_>) -> Result<()> {
+	let w = Wallet::load(wallet, builder.network)?;
+	let m = builder.build()?;
+
+	m.mint_item(&w, item).await
+}
+
+impl Miner {
+	async fn claim_container(
+		&self,
+		wallet: &Wallet,
+		container: &str,
+		bitworkc: Option<String>,
+		bitworkr: Option<String>,
+	) -> Result<()> {
+		realm::check_name(container, |c| c.is_ascii_lowercase() || c.is_ascii_digit())?;
+
+		let c = self.api.get_by_container(container).await?;
+
+		if c.status == "verified" {
+			Err(anyhow::anyhow!("container {container} has already been claimed"))?;
+		}
+		if !c.candidates.is_empty() {
+			tracing::warn!(
+				"container {container} has {} pending candidate(s); an earlier commit takes precedence",
+				c.candidates.len()
+			);
+		}
+
+		let nft = Nft {
+			args: {
+				let (time, nonce) = util::time_nonce();
+
+				NftArgs {
+					bitworkc,
+					bitworkr,
+					nonce,
+					request_container: Some(container.into()),
+					time,
+					..Default::default()
+				}
+			},
+			files: Vec::new(),
+		};
+		let r = self.mint_nft(wallet, nft).await?;
+
+		tracing::info!("claimed container {container} as {}", r.atomical_id());
+
+		Ok(())
+	}
+
+	async fn mint_item(&self, wallet: &Wallet, item: Item<'_>) -> Result<()> {
+		let Item { container, id, file, bitworkc, bitworkr, proof } = item;
+		let container_id = self
+			.api
+			.get_by_container(container)
+			.await?
+			.atomical_id
+			.ok_or_else(|| anyhow::anyhow!("container {container} not verified"))?;
+		let state = self.api.get_state(&container_id).await?;
+		let height = state.global.ok_or_else(|| anyhow::anyhow!("missing global info"))?.height;
+		let dmint = state
+			.result
+			.state
+			.latest
+			.dmint
+			.ok_or_else(|| anyhow::anyhow!("container {container} is not a dmint container"))?;
+
+		if dmint.mint_height > height + 1 {
+			Err(anyhow::anyhow!(
+				"container {container} can be minted from height {}",
+				dmint.mint_height
+			))?;
+		}
+
+		let rule = realm::match_rule(&dmint.rules, id)?
+			.ok_or_else(|| anyhow::anyhow!("no rule of {container} matches item {id}"))?;
+		let main = file
+			.file_name()
+			.and_then(|n| n.to_str())
+			.ok_or_else(|| anyhow::anyhow!("invalid file name {}", file.display()))?;
+		let data = fs::read(file)?;
+		let main_hash = sha256::Hash::hash(&data).to_string();
+		let v = self
+			.api
+			.get_container_item_validation(
+				container,
+				id,
+				bitworkc.unwrap_or(ANY),
+				bitworkr.unwrap_or(ANY),
+				main,
+				&main_hash,
+				&proof,
+			)
+			.await?;
+
+		if !v.proof_valid {
+			Err(anyhow::anyhow!("invalid proof of item {id} in {container}"))?;
+		}
+		if v.status.as_deref() == Some("verified") {
+			Err(anyhow::anyhow!("item {id} of {container} has already been claimed"))?;
+		}
+
+		// The bitwork of the item takes precedence over the one of the rule.
+		let bitworkc = required_bitwork(bitworkc, rule.bitworkc.as_deref());
+		let bitworkr = required_bitwork(bitworkr, rule.bitworkr.as_deref());
+		let nft = Nft {
+			args: {
+				let (time, nonce) = util::time_nonce();
+
+				NftArgs {
+					bitworkc,
+					bitworkr,
+					i: Some(true),
+					main: Some(main.into()),
+					nonce,
+					parent_container: Some(container_id),
+					proof: Some(proof),
+					request_dmitem: Some(id.into()),
+					time,
+					..Default::default()
+				}
+			},
+			files: vec![(main.into(), File::Raw { data })],
+		};
+		let r = self.mint_nft(wallet, nft).await?;
+
+		tracing::info!("minted item {id} of {container} as {}", r.atomical_id());
+
+		Ok(())
+	}
+}
+
+#[derive(Debug)]
+pub struct Item<'a> {
+	pub container: &'a str,
+	pub id: &'a str,
+	pub file: &'a Path,
+	pub bitworkc: Option<&'a str>,
+	pub bitworkr: Option<&'a str>,
+	pub proof: Vec<ProofItem>,
+}
+
+const ANY: &str = "any";
+
+fn required_bitwork(item: Option<&str>, rule: Option<&str>) -> Option<String> {
+	[item, rule].into_iter().flatten().find(|b| *b != ANY).map(Into::into)
+}
+#[test]
+fn required_bitwork_should_work() {
+	assert_eq!(required_bitwork(Some("7777"), Some("88")), Some("7777".into()));
+	assert_eq!(required_bitwork(Some(ANY), Some("88")), Some("88".into()));
+	assert_eq!(required_bitwork(None, Some(ANY)), None);
+	assert_eq!(required_bitwork(None, None), None);
+}
@@ -0,0 +1,228 @@
+// crates.io
+use bitcoin::{consensus::encode, hex::FromHex, Network, Script, Transaction};
+use serde_json::Value;
+// atomicalsir
+use crate::{prelude::*, util};
+use atomicals_electrumx::{transport::HttpOptions, Api, Client};
+
+pub async fn decode(
+	network: Network,
+	electrumx: &str,
+	http: &HttpOptions,
+	input: &str,
+) -> Result<()> {
+	let input = input.trim();
+	// A transaction ID is fetched from the electrumx, anything else is taken as raw hex.
+	let hex = if input.len() == 64 {
+		Client::new_with_http(network, electrumx, http)?.get_transaction(input).await?
+	} else {
+		input.into()
+	};
+
+	for r in decode_reveals(&Vec::from_hex(&hex)?)? {
+		println!("{}", serde_json::to_string_pretty(&r)?);
+	}
+
+	Ok(())
+}
+
+// Decode a transaction into the reveals of its inputs, or a tapscript into its reveal.
+fn decode_reveals(bytes: &[u8]) -> Result<Vec<Value>> {
+	if let Ok(tx) = encode::deserialize::<Transaction>(bytes) {
+		let rs = tx
+			.input
+			.iter()
+			.enumerate()
+			.filter_map(|(i, txin)| {
+				let (_, op, payload) = util::parse_reveal_script(txin.witness.tapscript()?).ok()?;
+
+				Some((i, op, payload))
+			})
+			.map(|(i, op, payload)| {
+				Ok(serde_json::json!({
+					"input": i,
+					"op": op,
+					"payload": util::cbor_to_json(&payload)?,
+				}))
+			})
+			.collect::<Result<Vec<_>>>()?;
+
+		if rs.is_empty() {
+			Err(anyhow::anyhow!("no reveal found in transaction {}", tx.txid()))?;
+		}
+
+		Ok(rs)
+	} else {
+		let (_, op, payload) = util::parse_reveal_script(Script::from_bytes(bytes))?;
+
+		Ok(vec![serde_json::json!({ "op": op, "payload": util::cbor_to_json(&payload)? })])
+	}
+}
+#[test]
+fn decode_reveals_should_work() {
+	// std
+	use std::str::FromStr;
+	// crates.io
+	use bitcoin::{absolute::LockTime, transaction::Version, OutPoint, TxIn, Witness};
+	// atomicalsir
+	use super::operation::*;
+
+	let xpk = util::keypair_from_wif("L4VgnxVoaPRaptd4yW19wwd7v9dzJvQn478AKwucbaQifPFBacrp")
+		.unwrap()
+		.x_only_public_key()
+		.0;
+	let decode = |op, payload: Vec<u8>| {
+		decode_reveals(util::build_reval_script(&xpk, op, &payload).as_bytes()).unwrap().remove(0)
+	};
+
+	assert_eq!(
+		decode(
+			"dmt",
+			util::cbor(&Dmt {
+				args: DmtArgs {
+					bitworkc: Some("aabbcc".into()),
+					bitworkr: None,
+					mint_ticker: "quark".into(),
+					nonce: 9999999,
+					time: 1704057427,
+				},
+			})
+			.unwrap()
+		),
+		serde_json::json!({
+			"op": "dmt",
+			"payload": {
+				"args": {
+					"bitworkc": "aabbcc",
+					"mint_ticker": "quark",
+					"nonce": 9999999,
+					"time": 1704057427
+				}
+			}
+		})
+	);
+	assert_eq!(
+		decode(
+			"ft",
+			util::cbor(&Ft {
+				args: FtArgs {
+					bitworkc: None,
+					bitworkr: None,
+					nonce: 1,
+					request_ticker: "atomicalsir".into(),
+					time: 2,
+				},
+				meta: Some(Meta { name: Some("atomicalsir".into()), ..Default::default() }),
+			})
+			.unwrap()
+		)["payload"],
+		serde_json::json!({
+			"args": { "nonce": 1, "request_ticker": "atomicalsir", "time": 2 },
+			"meta": { "name": "atomicalsir" }
+		})
+	);
+	assert_eq!(
+		decode(
+			"nft",
+			util::cbor(&Nft {
+				args: NftArgs {
+					bitworkr: Some("7777".into()),
+					nonce: 1,
+					time: 2,
+					..Default::default()
+				},
+				files: vec![(
+					"atom.txt".into(),
+					File::Typed { content_type: "text/plain".into(), data: b"atom".to_vec() },
+				)],
+			})
+			.unwrap()
+		)["payload"],
+		serde_json::json!({
+			"args": { "bitworkr": "7777", "nonce": 1, "time": 2 },
+			"atom.txt": { "$ct": "text/plain", "$d": "61746f6d" }
+		})
+	);
+	assert_eq!(
+		decode(
+			"nft",
+			util::cbor(&Nft {
+				args: NftArgs {
+					claim_type: Some("rule".into()),
+					nonce: 1,
+					parent_realm: Some("aai0".into()),
+					request_subrealm: Some("sub".into()),
+					time: 2,
+					..Default::default()
+				},
+				files: Vec::new(),
+			})
+			.unwrap()
+		)["payload"],
+		serde_json::json!({
+			"args": {
+				"claim_type": "rule",
+				"nonce": 1,
+				"parent_realm": "aai0",
+				"request_subrealm": "sub",
+				"time": 2
+			}
+		})
+	);
+	assert_eq!(
+		decode(
+			"nft",
+			util::cbor(&Nft {
+				args: NftArgs {
+					i: Some(true),
+					main: Some("1.txt".into()),
+					nonce: 1,
+					parent_container: Some("aai0".into()),
+					proof: Some(vec![ProofItem { d: "ff".into(), p: false }]),
+					request_dmitem: Some("1".into()),
+					time: 2,
+					..Default::default()
+				},
+				files: vec![("1.txt".into(), File::Raw { data: b"1".to_vec() })],
+			})
+			.unwrap()
+		)["payload"],
+		serde_json::json!({
+			"args": {
+				"i": true,
+				"main": "1.txt",
+				"nonce": 1,
+				"parent_container": "aai0",
+				"proof": [{ "d": "ff", "p": false }],
+				"request_dmitem": "1",
+				"time": 2
+			},
+			"1.txt": { "$b": "31" }
+		})
+	);
+
+	// Decode the reveal from the witness of a transaction.
+	let script = util::build_reval_script(&xpk, "dat", &util::cbor(&("atom",)).unwrap());
+	let tx = Transaction {
+		version: Version::ONE,
+		lock_time: LockTime::ZERO,
+		input: vec![
+			TxIn::default(),
+			TxIn {
+				previous_output: OutPoint::from_str(
+					"1809bfb8f69a1de200b5a5b2e96afef9f7417ee0772363c7a7cb6591eb1a9b8b:0",
+				)
+				.unwrap(),
+				witness: Witness::from_slice(&[&[0; 64][..], script.as_bytes(), &[0xc0; 33]]),
+				..Default::default()
+			},
+		],
+		output: Vec::new(),
+	};
+
+	assert_eq!(
+		decode_reveals(&encode::serialize(&tx)).unwrap(),
+		[serde_json::json!({ "input": 1, "op": "dat", "payload": ["atom"] })]
+	);
+	assert!(decode_reveals(&[0x51]).is_err());
+}
@@ -0,0 +1,178 @@
+// std
+use std::{path::Path, time::Duration};
+// crates.io
+use bitcoin::{Amount, TxOut};
+use tokio::time::Instant;
+// atomicalsir
+use super::{
+	operation::{Ft, FtArgs, Meta, Operation},
+	Miner, MinerBuilder, Wallet,
+};
+use crate::{prelude::*, util};
+use atomicals_electrumx::Api;
+
+pub async fn deploy(
+	builder: MinerBuilder<'_>,
+	wallet: &Path,
+	ticker: &str,
+	supply: u64,
+	bitworkc: Option<String>,
+	meta: Meta,
+) -> Result<()> {
+	let w = Wallet::load(wallet, builder.network)?;
+	let m = builder.build()?;
+
+	m.deploy_ft(&w, ticker, supply, bitworkc, meta).await
+}
+
+impl Miner {
+	async fn deploy_ft(
+		&self,
+		wallet: &Wallet,
+		ticker: &str,
+		supply: u64,
+		bitworkc: Option<String>,
+		meta: Meta,
+	) -> Result<()> {
+		check_ticker(ticker)?;
+
+		if supply < Self::DUST {
+			Err(anyhow::anyhow!("supply must be at least {} to be spendable", Self::DUST))?;
+		}
+
+		let t = self.api.get_by_ticker(ticker).await?;
+
+		if t.status == "verified" {
+			Err(anyhow::anyhow!("ticker {ticker} has already been granted"))?;
+		}
+		if !t.candidates.is_empty() {
+			tracing::warn!(
+				"ticker {ticker} has {} pending candidate(s); an earlier commit takes precedence",
+				t.candidates.len()
+			);
+		}
+
+		let additional_outputs = vec![TxOut {
+			value: Amount::from_sat(supply),
+			script_pubkey: wallet.stash.address.script_pubkey(),
+		}];
+		let op = Operation::Ft(Ft {
+			args: {
+				let (time, nonce) = util::time_nonce();
+
+				FtArgs {
+					bitworkc: bitworkc.clone(),
+					bitworkr: None,
+					nonce,
+					request_ticker: ticker.into(),
+					time,
+				}
+			},
+			meta: if meta.is_empty() { None } else { Some(meta) },
+		});
+		let d =
+			self.build_data(wallet, &op, Vec::new(), additional_outputs, bitworkc, None).await?;
+		let r = self.execute(wallet, d).await?;
+
+		if !r.revealed {
+			Err(anyhow::anyhow!("reveal transaction {} was not broadcast", r.reveal_txid))?;
+		}
+
+		let id = r.atomical_id();
+
+		tracing::info!("deployed {ticker} as {id}");
+
+		self.wait_until_ticker_granted(ticker, &id).await
+	}
+
+	async fn wait_until_ticker_granted(&self, ticker: &str, atomical_id: &str) -> Result<()> {
+		let since = Instant::now();
+
+		loop {
+			let t = self.api.get_by_ticker(ticker).await?;
+
+			if t.status == "verified" {
+				return match t.atomical_id {
+					Some(id) if id == atomical_id => {
+						tracing::info!("ticker {ticker} has been granted to {atomical_id}");
+
+						Ok(())
+					},
+					id => Err(anyhow::anyhow!(
+						"ticker {ticker} has been granted to {} instead of {atomical_id}",
+						id.unwrap_or_default()
+					)),
+				};
+			}
+
+			tracing::info!(
+				"awaiting ticker {ticker} to be granted to {atomical_id}; current status: {}",
+				t.status
+			);
+
+			self.poll_later(since, Duration::from_secs(60), &format!("ticker {ticker}")).await?;
+		}
+	}
+}
+
+fn check_ticker(ticker: &str) -> Result<()> {
+	if ticker.is_empty()
+		|| ticker.len() > 21
+		|| !ticker.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+	{
+		Err(anyhow::anyhow!("invalid ticker {ticker}; expected 1 to 21 characters of [a-z0-9]"))?;
+	}
+
+	Ok(())
+}
+#[test]
+fn wait_until_ticker_granted_should_work() {
+	// crates.io
+	use bitcoin::Network;
+	use tokio::runtime::Runtime;
+	// atomicalsir
+	use super::Backend;
+	use atomicals_electrumx::{
+		mock::{Ft, Mock},
+		transport::HttpOptions,
+	};
+
+	Runtime::new().unwrap().block_on(async {
+		let m = Mock::start().await;
+		let id = m.state().add_ft(Ft::easy("atomicalsir")).to_string();
+		let electrumx = [m.uri.clone()];
+		let miner = MinerBuilder {
+			thread: 1,
+			network: Network::Testnet,
+			fee_bound: &util::FeeBound { min: 1, max: 100 },
+			electrumx: &electrumx,
+			wait_timeout: Some(Duration::from_millis(10)),
+			bitcoind: None,
+			bitcoind_cookie: None,
+			esplora: None,
+			chain_backend: Backend::Electrumx,
+			fee_backend: Backend::Esplora,
+			http: HttpOptions::default(),
+			atomical_id: None,
+		}
+		.build()
+		.unwrap();
+
+		miner.wait_until_ticker_granted("atomicalsir", &id).await.unwrap();
+
+		assert!(miner.wait_until_ticker_granted("atomicalsir", "00i0").await.is_err());
+
+		let e = miner.wait_until_ticker_granted("sir", &id).await.unwrap_err();
+
+		assert_eq!(e.to_string(), "timed out after 10ms awaiting ticker sir");
+	});
+}
+#[test]
+fn check_ticker_should_work() {
+	assert!(check_ticker("atomicalsir").is_ok());
+	assert!(check_ticker("quark2").is_ok());
+	assert!(check_ticker("").is_err());
+	assert!(check_ticker("Quark").is_err());
+	assert!(check_ticker("qu-ark").is_err());
+	assert!(check_ticker("abcdefghijklmnopqrstuv").is_err());
+}
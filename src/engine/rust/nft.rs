@@ -0,0 +1,76 @@
+// std
+use std::{fs, path::Path};
+// crates.io
+use bitcoin::{Amount, TxOut};
+// atomicalsir
+use super::{
+	operation::{File, Nft, NftArgs, Operation},
+	Miner, MinerBuilder, Receipt, Wallet,
+};
+use crate::{prelude::*, util};
+
+pub async fn mint(
+	builder: MinerBuilder<'_>,
+	wallet: &Path,
+	file: &Path,
+	content_type: &str,
+	bitworkc: Option<String>,
+	bitworkr: Option<String>,
+) -> Result<()> {
+	let w = Wallet::load(wallet, builder.network)?;
+	let m = builder.build()?;
+	let name = file
+		.file_name()
+		.and_then(|n| n.to_str())
+		.ok_or_else(|| anyhow::anyhow!("invalid file name {}", file.display()))?;
+
+	if name == "args" {
+		Err(anyhow::anyhow!("file name `args` is reserved"))?;
+	}
+
+	let nft = Nft {
+		args: {
+			let (time, nonce) = util::time_nonce();
+
+			NftArgs { bitworkc, bitworkr, nonce, time, ..Default::default() }
+		},
+		files: vec![(
+			name.into(),
+			File::Typed { content_type: content_type.into(), data: fs::read(file)? },
+		)],
+	};
+	let r = m.mint_nft(&w, nft).await?;
+
+	tracing::info!("minted NFT {}", r.atomical_id());
+
+	Ok(())
+}
+
+impl Miner {
+	// Mint the NFT with the bitwork specified in its arguments.
+	pub(super) async fn mint_nft(&self, wallet: &Wallet, nft: Nft) -> Result<Receipt> {
+		let additional_outputs = vec![TxOut {
+			value: Amount::from_sat(Self::NFT_VALUE),
+			script_pubkey: wallet.stash.address.script_pubkey(),
+		}];
+		let bitworkc = nft.args.bitworkc.clone();
+		let bitworkr = nft.args.bitworkr.clone();
+		let d = self
+			.build_data(
+				wallet,
+				&Operation::Nft(nft),
+				Vec::new(),
+				additional_outputs,
+				bitworkc,
+				bitworkr,
+			)
+			.await?;
+		let r = self.execute(wallet, d).await?;
+
+		if !r.revealed {
+			Err(anyhow::anyhow!("reveal transaction {} was not broadcast", r.reveal_txid))?;
+		}
+
+		Ok(r)
+	}
+}
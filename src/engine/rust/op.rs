@@ -0,0 +1,145 @@
+// std
+use std::{fs, path::Path};
+// crates.io
+use bitcoin::{Amount, OutPoint, TxOut};
+// atomicalsir
+use super::{
+	operation::{Operation, Raw},
+	Miner, MinerBuilder, Wallet,
+};
+use crate::{prelude::*, util};
+use atomicals_electrumx::{r#type::AtomicalId, Api};
+
+pub async fn operate(
+	builder: MinerBuilder<'_>,
+	wallet: &Path,
+	op: Op,
+	payload: &Path,
+	atomical_id: Option<&str>,
+	bitworkc: Option<String>,
+	bitworkr: Option<String>,
+) -> Result<()> {
+	let w = Wallet::load(wallet, builder.network)?;
+	let m = builder.build()?;
+	let op = op.operation(load_payload(payload)?);
+
+	m.operate(&w, op, atomical_id, bitworkc, bitworkr).await
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+	// Store immutable data.
+	Dat,
+	// Modify the state of an atomical.
+	Mod,
+	// Emit an event message of an atomical.
+	Evt,
+}
+impl Op {
+	fn operation(self, payload: Vec<u8>) -> Operation {
+		match self {
+			Op::Dat => Operation::Dat(Raw(payload)),
+			Op::Mod => Operation::Mod(Raw(payload)),
+			Op::Evt => Operation::Evt(Raw(payload)),
+		}
+	}
+}
+
+impl Miner {
+	async fn operate(
+		&self,
+		wallet: &Wallet,
+		op: Operation,
+		atomical_id: Option<&str>,
+		bitworkc: Option<String>,
+		bitworkr: Option<String>,
+	) -> Result<()> {
+		let stash_spk = wallet.stash.address.script_pubkey();
+		let (additional_inputs, additional_outputs) = match (&op, atomical_id) {
+			(Operation::Dat(_), None) => (
+				Vec::new(),
+				vec![TxOut { value: Amount::from_sat(Self::NFT_VALUE), script_pubkey: stash_spk }],
+			),
+			(Operation::Dat(_), Some(_)) =>
+				Err(anyhow::anyhow!("`dat` does not spend an atomical"))?,
+			(_, None) => Err(anyhow::anyhow!("`{}` requires an atomical to spend", op.op_type()))?,
+			(_, Some(id)) => {
+				let id = id.parse::<AtomicalId>()?;
+				let u = self
+					.api
+					.get_unspent_address(wallet.stash.address.to_string())
+					.await?
+					.into_iter()
+					.find(|u| u.carries(&id))
+					.ok_or_else(|| anyhow::anyhow!("atomical {id} is not held by the stash"))?;
+
+				if u.atomicals.len() > 1 {
+					tracing::warn!(
+						"UTXO {}:{} also carries {:?}; they are carried forward together",
+						u.txid,
+						u.vout,
+						u.atomicals
+					);
+				}
+
+				let carried = TxOut { value: Amount::from_sat(u.value), script_pubkey: stash_spk };
+
+				// The atomical is spent by the second input of the reveal. The reveal has no
+				// spendable output but the first one, so it lands there under both the
+				// same-index and the fallback rules.
+				(vec![(OutPoint::new(u.txid.parse()?, u.vout), carried.clone())], vec![carried])
+			},
+		};
+		let d = self
+			.build_data(wallet, &op, additional_inputs, additional_outputs, bitworkc, bitworkr)
+			.await?;
+		let r = self.execute(wallet, d).await?;
+
+		if !r.revealed {
+			Err(anyhow::anyhow!("reveal transaction {} was not broadcast", r.reveal_txid))?;
+		}
+
+		tracing::info!("`{}` revealed in {}", op.op_type(), r.reveal_txid);
+
+		Ok(())
+	}
+}
+
+// Take a CBOR file as is and encode anything else as JSON.
+fn load_payload(path: &Path) -> Result<Vec<u8>> {
+	let bytes = fs::read(path)?;
+
+	if path.extension().is_some_and(|e| e == "cbor") {
+		ciborium::from_reader::<ciborium::Value, _>(bytes.as_slice())
+			.map_err(|e| anyhow::anyhow!("invalid CBOR payload due to {e}"))?;
+
+		Ok(bytes)
+	} else {
+		util::cbor(&serde_json::from_slice::<serde_json::Value>(&bytes)?)
+	}
+}
+#[test]
+fn load_payload_should_work() {
+	let dir = std::env::temp_dir()
+		.join(format!("atomicalsir-load-payload-should-work-{}", std::process::id()));
+
+	fs::create_dir_all(&dir).unwrap();
+
+	let json = dir.join("payload.json");
+	let cbor = dir.join("payload.cbor");
+
+	fs::write(&json, r#"{"$path":"/a","b":[1]}"#).unwrap();
+	fs::write(&cbor, [0xa1, 0x61, 0x61, 0x01]).unwrap();
+
+	assert_eq!(
+		load_payload(&json).unwrap(),
+		array_bytes::hex2bytes_unchecked("a2652470617468622f6161628101")
+	);
+	assert_eq!(load_payload(&cbor).unwrap(), [0xa1, 0x61, 0x61, 0x01]);
+
+	fs::write(&cbor, [0xa1]).unwrap();
+
+	assert!(load_payload(&cbor).is_err());
+
+	fs::remove_dir_all(&dir).unwrap();
+}
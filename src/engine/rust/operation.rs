@@ -0,0 +1,329 @@
+// std
+use std::collections::BTreeMap;
+// crates.io
+use bitcoin::{ScriptBuf, XOnlyPublicKey};
+use serde::{ser::SerializeMap, Deserialize, Serialize, Serializer};
+// atomicalsir
+use crate::{prelude::*, util};
+
+// The fields of each payload are declared in alphabetical order. The indexer decodes a payload
+// as a map, so the key order changes the bytes but not the meaning. atomicals-js writes `time`,
+// `nonce` and the bitworks first instead, as the `args` of the `dft` revealed in the testnet
+// transaction 8b40e05c14326ae372bf5f9f89e746446015376c25ab564d0b740468dd38af43 show.
+//
+// So the test vectors pin the encoding of these types, they are not taken from real reveals.
+
+#[derive(Debug)]
+pub enum Operation {
+	// Mint a decentralized FT.
+	Dmt(Dmt),
+	// Deploy a fixed supply FT.
+	Ft(Ft),
+	// Mint an NFT, which also claims realms, subrealms, containers and container items.
+	Nft(Nft),
+	// Modify the state of an atomical.
+	Mod(Raw),
+	// Emit an event message of an atomical.
+	Evt(Raw),
+	// Store immutable data.
+	Dat(Raw),
+	// Split the FTs located at the same UTXO.
+	Y(Split),
+	// Color the outputs with the given FT values.
+	Z(CustomColor),
+}
+impl Operation {
+	pub fn op_type(&self) -> &'static str {
+		match self {
+			Self::Dmt(_) => "dmt",
+			Self::Ft(_) => "ft",
+			Self::Nft(_) => "nft",
+			Self::Mod(_) => "mod",
+			Self::Evt(_) => "evt",
+			Self::Dat(_) => "dat",
+			Self::Y(_) => "y",
+			Self::Z(_) => "z",
+		}
+	}
+
+	pub fn payload(&self) -> Result<Vec<u8>> {
+		match self {
+			Self::Dmt(p) => util::cbor(p),
+			Self::Ft(p) => util::cbor(p),
+			Self::Nft(p) => util::cbor(p),
+			Self::Mod(p) | Self::Evt(p) | Self::Dat(p) => Ok(p.0.clone()),
+			Self::Y(p) => util::cbor(p),
+			Self::Z(p) => util::cbor(p),
+		}
+	}
+
+	pub fn reveal_script(&self, x_only_public_key: &XOnlyPublicKey) -> Result<ScriptBuf> {
+		Ok(util::build_reval_script(x_only_public_key, self.op_type(), &self.payload()?))
+	}
+}
+#[test]
+fn reveal_script_should_work() {
+	let xpk = util::keypair_from_wif("L4VgnxVoaPRaptd4yW19wwd7v9dzJvQn478AKwucbaQifPFBacrp")
+		.unwrap()
+		.x_only_public_key()
+		.0;
+	let dmt = Operation::Dmt(Dmt {
+		args: DmtArgs {
+			bitworkc: Some("aabbcc".into()),
+			bitworkr: None,
+			mint_ticker: "quark".into(),
+			nonce: 9999999,
+			time: 1704057427,
+		},
+	});
+
+	assert_eq!(
+		array_bytes::bytes2hex("", dmt.reveal_script(&xpk).unwrap()),
+		"207e41d0ce6e41328e17ec13076603fc9d7a1d41fb1b497af09cdfbf9b648f7480ac00630461746f6d03646d743ea16461726773a468626974776f726b63666161626263636b6d696e745f7469636b657265717561726b656e6f6e63651a0098967f6474696d651a6591da5368"
+	);
+
+	// The raw payload is inscribed as is.
+	let dat = Operation::Dat(Raw(vec![0xa0]));
+
+	assert_eq!(
+		util::parse_reveal_script(&dat.reveal_script(&xpk).unwrap()).unwrap(),
+		(xpk, "dat".into(), vec![0xa0])
+	);
+}
+
+#[derive(Debug, Serialize)]
+pub struct Dmt {
+	pub args: DmtArgs,
+}
+#[derive(Debug, Serialize)]
+pub struct DmtArgs {
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub bitworkc: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub bitworkr: Option<String>,
+	pub mint_ticker: String,
+	pub nonce: u64,
+	pub time: u64,
+}
+#[test]
+fn dmt_should_work() {
+	assert_eq!(
+		util::cbor(&Dmt {
+			args: DmtArgs {
+				bitworkc: Some("aabbcc".into()),
+				bitworkr: Some("6666".into()),
+				mint_ticker: "quark".into(),
+				nonce: 9999999,
+				time: 1704057427
+			}
+		})
+		.unwrap(),
+		array_bytes::hex2bytes_unchecked("a16461726773a568626974776f726b636661616262636368626974776f726b7264363636366b6d696e745f7469636b657265717561726b656e6f6e63651a0098967f6474696d651a6591da53")
+	);
+}
+
+#[derive(Debug, Serialize)]
+pub struct Ft {
+	pub args: FtArgs,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub meta: Option<Meta>,
+}
+#[derive(Debug, Serialize)]
+pub struct FtArgs {
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub bitworkc: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub bitworkr: Option<String>,
+	pub nonce: u64,
+	pub request_ticker: String,
+	pub time: u64,
+}
+#[test]
+fn ft_should_work() {
+	assert_eq!(
+		util::cbor(&Ft {
+			args: FtArgs {
+				bitworkc: Some("1234".into()),
+				bitworkr: None,
+				nonce: 9999999,
+				request_ticker: "atomicalsir".into(),
+				time: 1704057427
+			},
+			meta: Some(Meta {
+				description: Some("Atomicals mining manager.".into()),
+				name: Some("atomicalsir".into()),
+				..Default::default()
+			})
+		})
+		.unwrap(),
+		array_bytes::hex2bytes_unchecked("a26461726773a468626974776f726b636431323334656e6f6e63651a0098967f6e726571756573745f7469636b65726b61746f6d6963616c7369726474696d651a6591da53646d657461a26b6465736372697074696f6e781941746f6d6963616c73206d696e696e67206d616e616765722e646e616d656b61746f6d6963616c736972")
+	);
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct Meta {
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub description: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub legal: Option<Legal>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub name: Option<String>,
+}
+impl Meta {
+	pub fn is_empty(&self) -> bool {
+		self.description.is_none() && self.legal.is_none() && self.name.is_none()
+	}
+}
+#[derive(Debug, Serialize)]
+pub struct Legal {
+	pub terms: String,
+}
+
+#[derive(Debug)]
+pub struct Nft {
+	pub args: NftArgs,
+	pub files: Vec<(String, File)>,
+}
+// Serialize the files as the sibling entries of `args` with a definite length.
+impl Serialize for Nft {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		let mut m = serializer.serialize_map(Some(1 + self.files.len()))?;
+
+		m.serialize_entry("args", &self.args)?;
+
+		for (n, f) in &self.files {
+			m.serialize_entry(n, f)?;
+		}
+
+		m.end()
+	}
+}
+// The arguments of a plain NFT, along with the claims of the names.
+#[derive(Debug, Default, Serialize)]
+pub struct NftArgs {
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub bitworkc: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub bitworkr: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub claim_type: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub i: Option<bool>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub main: Option<String>,
+	pub nonce: u64,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub parent_container: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub parent_realm: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub proof: Option<Vec<ProofItem>>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub request_container: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub request_dmitem: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub request_realm: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub request_subrealm: Option<String>,
+	pub time: u64,
+}
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum File {
+	Typed {
+		#[serde(rename = "$ct")]
+		content_type: String,
+		#[serde(rename = "$d", with = "serde_bytes")]
+		data: Vec<u8>,
+	},
+	Raw {
+		#[serde(rename = "$b", with = "serde_bytes")]
+		data: Vec<u8>,
+	},
+}
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ProofItem {
+	pub d: String,
+	pub p: bool,
+}
+#[test]
+fn nft_should_work() {
+	assert_eq!(
+		util::cbor(&Nft {
+			args: NftArgs {
+				bitworkc: Some("1234".into()),
+				nonce: 9999999,
+				time: 1704057427,
+				..Default::default()
+			},
+			files: vec![(
+				"atom.txt".into(),
+				File::Typed { content_type: "text/plain".into(), data: b"atom".to_vec() }
+			)],
+		})
+		.unwrap(),
+		array_bytes::hex2bytes_unchecked("a26461726773a368626974776f726b636431323334656e6f6e63651a0098967f6474696d651a6591da536861746f6d2e747874a2632463746a746578742f706c61696e6224644461746f6d")
+	);
+	assert_eq!(
+		util::cbor(&Nft {
+			args: NftArgs {
+				i: Some(true),
+				main: Some("1.txt".into()),
+				nonce: 9999999,
+				parent_container: Some("aai0".into()),
+				proof: Some(vec![ProofItem { d: "ff".into(), p: true }]),
+				request_dmitem: Some("1".into()),
+				time: 1704057427,
+				..Default::default()
+			},
+			files: vec![("1.txt".into(), File::Raw { data: b"1".to_vec() })],
+		})
+		.unwrap(),
+		array_bytes::hex2bytes_unchecked("a26461726773a76169f5646d61696e65312e747874656e6f6e63651a0098967f70706172656e745f636f6e7461696e657264616169306570726f6f6681a261646266666170f56e726571756573745f646d6974656d61316474696d651a6591da5365312e747874a16224624131")
+	);
+}
+
+// A CBOR payload provided by the user, which is inscribed as is.
+#[derive(Debug)]
+pub struct Raw(pub Vec<u8>);
+
+// The value to skip of each atomical before coloring the outputs.
+#[derive(Debug, Default, Serialize)]
+pub struct Split(pub BTreeMap<String, u64>);
+
+// The value of each output colored by each atomical.
+#[derive(Debug, Default)]
+pub struct CustomColor(pub BTreeMap<String, BTreeMap<u32, u64>>);
+// The output indexes are keyed as strings as atomicals-js does.
+impl Serialize for CustomColor {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		let mut m = serializer.serialize_map(Some(self.0.len()))?;
+
+		for (id, os) in &self.0 {
+			m.serialize_entry(
+				id,
+				&os.iter().map(|(i, v)| (i.to_string(), v)).collect::<BTreeMap<_, _>>(),
+			)?;
+		}
+
+		m.end()
+	}
+}
+#[test]
+fn yz_should_work() {
+	assert_eq!(
+		util::cbor(&Split([("aai0".into(), 1000)].into())).unwrap(),
+		array_bytes::hex2bytes_unchecked("a164616169301903e8")
+	);
+	assert_eq!(
+		util::cbor(&CustomColor([("aai0".into(), [(0, 600), (1, 400)].into())].into())).unwrap(),
+		array_bytes::hex2bytes_unchecked("a16461616930a261301902586131190190")
+	);
+}
@@ -0,0 +1,368 @@
+// std
+use std::{path::Path, time::Duration};
+// crates.io
+use bitcoin::{
+	consensus::encode, key::TapTweak, opcodes::all::OP_RETURN, script::PushBytes,
+	secp256k1::Secp256k1, Amount, OutPoint, Psbt, Script, ScriptBuf, Sequence, Transaction, TxIn,
+	TxOut,
+};
+use regex::Regex;
+use tokio::time::Instant;
+// atomicalsir
+use super::{
+	operation::{Nft, NftArgs},
+	sign_commit_psbt, Miner, MinerBuilder, Wallet,
+};
+use crate::{prelude::*, util};
+use atomicals_electrumx::{
+	r#type::{AtomicalId, MintRule},
+	Api,
+};
+
+pub async fn claim_realm(
+	builder: MinerBuilder<'_>,
+	wallet: &Path,
+	realm: &str,
+	bitworkc: Option<String>,
+	bitworkr: Option<String>,
+) -> Result<()> {
+	let w = Wallet::load(wallet, builder.network)?;
+	let m = builder.build()?;
+
+	m.claim_realm(&w, realm, bitworkc, bitworkr).await
+}
+
+pub async fn claim_subrealm(
+	builder: MinerBuilder<'_>,
+	wallet: &Path,
+	full_name: &str,
+) -> Result<()> {
+	let w = Wallet::load(wallet, builder.network)?;
+	let m = builder.build()?;
+
+	m.claim_subrealm(&w, full_name).await
+}
+
+impl Miner {
+	// OP_RETURN <atom> <p> <36-byte atomical ID>.
+	const PAYMENT_OP_RETURN_BYTES: f64 = 8. + 1. + 45.;
+
+	async fn claim_realm(
+		&self,
+		wallet: &Wallet,
+		realm: &str,
+		bitworkc: Option<String>,
+		bitworkr: Option<String>,
+	) -> Result<()> {
+		check_realm(realm)?;
+
+		let r = self.api.get_by_realm(realm).await?;
+
+		if r.status == "verified" {
+			Err(anyhow::anyhow!("realm {realm} has already been claimed"))?;
+		}
+		if !r.candidates.is_empty() {
+			tracing::warn!(
+				"realm {realm} has {} pending candidate(s); an earlier commit takes precedence",
+				r.candidates.len()
+			);
+		}
+
+		let nft = Nft {
+			args: {
+				let (time, nonce) = util::time_nonce();
+
+				NftArgs {
+					bitworkc,
+					bitworkr,
+					nonce,
+					request_realm: Some(realm.into()),
+					time,
+					..Default::default()
+				}
+			},
+			files: Vec::new(),
+		};
+		let r = self.mint_nft(wallet, nft).await?;
+
+		tracing::info!("claimed realm {realm} as {}", r.atomical_id());
+
+		Ok(())
+	}
+
+	async fn claim_subrealm(&self, wallet: &Wallet, full_name: &str) -> Result<()> {
+		let (parent, subrealm) = full_name
+			.rsplit_once('.')
+			.ok_or_else(|| anyhow::anyhow!("expected <PARENT>.<SUBREALM> found {full_name}"))?;
+		let mut names = full_name.split('.');
+
+		check_realm(names.next().unwrap_or_default())?;
+		names.try_for_each(check_subrealm)?;
+
+		let info = self.api.get_realm_info(full_name).await?.result;
+
+		if info.found_full_realm_name.as_deref() == Some(full_name) {
+			Err(anyhow::anyhow!("subrealm {full_name} has already been claimed"))?;
+		}
+		if info.found_full_realm_name.as_deref() != Some(parent)
+			|| info.missing_name_parts.as_deref() != Some(subrealm)
+		{
+			Err(anyhow::anyhow!("parent realm {parent} has not been claimed"))?;
+		}
+		if !info.nearest_parent_realm_subrealm_mint_allowed {
+			Err(anyhow::anyhow!("parent realm {parent} does not allow minting subrealms"))?;
+		}
+
+		let parent_id = info
+			.nearest_parent_realm_atomical_id
+			.ok_or_else(|| anyhow::anyhow!("parent realm {parent} has no atomical ID"))?;
+		let rules = info.nearest_parent_realm_subrealm_mint_rules.map(|r| r.current_height_rules);
+		let rule = match_rule(&rules.unwrap_or_default(), subrealm)?
+			.ok_or_else(|| anyhow::anyhow!("no mint rule of {parent} matches {subrealm}"))?;
+
+		tracing::info!("subrealm {subrealm} matches the rule {rule:?}");
+
+		let nft = Nft {
+			args: {
+				let (time, nonce) = util::time_nonce();
+
+				NftArgs {
+					bitworkc: rule.bitworkc.clone(),
+					bitworkr: rule.bitworkr.clone(),
+					claim_type: Some("rule".into()),
+					nonce,
+					parent_realm: Some(parent_id),
+					request_subrealm: Some(subrealm.into()),
+					time,
+					..Default::default()
+				}
+			},
+			files: Vec::new(),
+		};
+		let r = self.mint_nft(wallet, nft).await?;
+		let id = r.atomical_id();
+
+		tracing::info!("claimed subrealm {full_name} as {id}");
+
+		self.wait_until_subrealm_paid(wallet, &id, &rule).await
+	}
+
+	async fn wait_until_subrealm_paid(
+		&self,
+		wallet: &Wallet,
+		atomical_id: &str,
+		rule: &MintRule,
+	) -> Result<()> {
+		let since = Instant::now();
+		let mut paid = false;
+
+		loop {
+			let r = self.api.get_atomical(atomical_id).await?;
+			let height = r.global.ok_or_else(|| anyhow::anyhow!("missing global info"))?.height;
+			let a = r.result;
+			let status = a.request_subrealm_status.map(|s| s.status).unwrap_or_default();
+
+			match status.as_str() {
+				"verified" => {
+					tracing::info!("subrealm {atomical_id} has been verified");
+
+					return Ok(());
+				},
+				"pending_awaiting_payment" if !paid => {
+					if let Some(c) =
+						a.subrealm_candidates.iter().find(|c| c.atomical_id == atomical_id)
+					{
+						let from = c.make_payment_from_height.unwrap_or_default();
+						let due = c.payment_due_no_later_than_height.unwrap_or(u64::MAX);
+
+						if height > due {
+							Err(anyhow::anyhow!(
+								"payment of {atomical_id} was due at height {due}; current height is {height}"
+							))?;
+						}
+						if height >= from {
+							self.pay_subrealm(
+								wallet,
+								atomical_id,
+								c.applicable_rule.as_ref().unwrap_or(rule),
+							)
+							.await?;
+
+							paid = true;
+						}
+					}
+				},
+				s if s.starts_with("expired") || s.starts_with("rejected") =>
+					Err(anyhow::anyhow!("subrealm {atomical_id} is {s}"))?,
+				_ => (),
+			}
+
+			tracing::info!(
+				"awaiting subrealm {atomical_id} to be verified; current status: {status} at height {height}"
+			);
+
+			self.poll_later(since, Duration::from_secs(60), &format!("subrealm {atomical_id}"))
+				.await?;
+		}
+	}
+
+	async fn pay_subrealm(
+		&self,
+		wallet: &Wallet,
+		atomical_id: &str,
+		rule: &MintRule,
+	) -> Result<()> {
+		let mut outputs = rule
+			.o
+			.iter()
+			.map(|(s, o)| {
+				if o.atomical_id().is_some() {
+					Err(anyhow::anyhow!("ARC20 payments are not supported"))?;
+				}
+
+				Ok(TxOut {
+					value: Amount::from_sat(o.value()),
+					script_pubkey: ScriptBuf::from_hex(s)?,
+				})
+			})
+			.collect::<Result<Vec<_>>>()?;
+
+		let satsbyte = self.satsbyte().await? as f64;
+		let fee = (satsbyte
+			* (Self::BASE_BYTES
+				+ Self::INPUT_BYTES_BASE
+				// Rule outputs and the refund.
+				+ (outputs.len() + 1) as f64 * Self::OUTPUT_BYTES_BASE
+				+ Self::PAYMENT_OP_RETURN_BYTES))
+			.ceil() as u64;
+		let amount = outputs.iter().map(|o| o.value.to_sat()).sum::<u64>();
+
+		outputs.push(TxOut {
+			value: Amount::ZERO,
+			script_pubkey: payment_marker_script(
+				&atomical_id.parse::<AtomicalId>()?.to_location_bytes(),
+			),
+		});
+
+		let funding_spk = wallet.funding.address.script_pubkey();
+		let funding_utxo =
+			self.wait_until_utxo(wallet.funding.address.to_string(), amount + fee).await?;
+		let refund = funding_utxo.value - amount - fee;
+
+		if refund >= Self::DUST {
+			outputs.push(TxOut {
+				value: Amount::from_sat(refund),
+				script_pubkey: funding_spk.clone(),
+			});
+		}
+
+		let secp = Secp256k1::new();
+		let prevouts =
+			[TxOut { value: Amount::from_sat(funding_utxo.value), script_pubkey: funding_spk }];
+		let mut psbt = Psbt::from_unsigned_tx(Transaction {
+			version: Self::VERSION,
+			lock_time: Self::LOCK_TIME,
+			input: vec![TxIn {
+				previous_output: OutPoint::new(funding_utxo.txid.parse()?, funding_utxo.vout),
+				sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+				..Default::default()
+			}],
+			output: outputs,
+		})?;
+
+		sign_commit_psbt(
+			&secp,
+			&wallet.funding.pair.tap_tweak(&secp, None).to_inner(),
+			&wallet.funding.x_only_public_key,
+			&mut psbt,
+			&prevouts,
+		)?;
+
+		let tx = psbt.extract_tx_unchecked_fee_rate();
+		let tx_hex = encode::serialize_hex(&tx);
+
+		tracing::info!("broadcasting payment transaction {} for {atomical_id}", tx.txid());
+		tracing::debug!("{tx:#?}");
+		tracing::info!("{tx_hex}");
+
+		self.broadcast(&tx_hex).await?;
+
+		Ok(())
+	}
+}
+
+fn check_realm(name: &str) -> Result<()> {
+	check_name(name, |c| c.is_ascii_lowercase())
+		.map_err(|_| anyhow::anyhow!("invalid realm {name}; expected ^[a-z][a-z0-9-]{{0,63}}$"))
+}
+fn check_subrealm(name: &str) -> Result<()> {
+	check_name(name, |c| c.is_ascii_lowercase() || c.is_ascii_digit()).map_err(|_| {
+		anyhow::anyhow!("invalid subrealm {name}; expected ^[a-z0-9][a-z0-9-]{{0,63}}$")
+	})
+}
+pub(super) fn check_name(name: &str, first: fn(char) -> bool) -> Result<()> {
+	let mut cs = name.chars();
+
+	if name.len() > 64
+		|| name.ends_with('-')
+		|| !cs.next().map(first).unwrap_or_default()
+		|| !cs.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+	{
+		Err(anyhow::anyhow!("invalid name {name}"))?;
+	}
+
+	Ok(())
+}
+#[test]
+fn check_name_should_work() {
+	assert!(check_realm("atomicalsir").is_ok());
+	assert!(check_realm("hack-ink").is_ok());
+	assert!(check_realm("0x").is_err());
+	assert!(check_realm("hack-").is_err());
+	assert!(check_realm("Hack").is_err());
+	assert!(check_realm("").is_err());
+	assert!(check_realm(&"a".repeat(65)).is_err());
+	assert!(check_subrealm("0x").is_ok());
+	assert!(check_subrealm("sub.realm").is_err());
+}
+
+// The electrumx matches the pattern from the beginning of the name.
+pub(super) fn match_rule(rules: &[MintRule], name: &str) -> Result<Option<MintRule>> {
+	for r in rules {
+		if Regex::new(&format!("^(?:{})", r.p))?.is_match(name) {
+			return Ok(Some(r.to_owned()));
+		}
+	}
+
+	Ok(None)
+}
+#[test]
+fn match_rule_should_work() {
+	let rules = serde_json::from_str::<Vec<MintRule>>(
+		r#"[
+			{ "p": "[0-9]{3}$", "o": { "5120aa": { "v": 1000 } } },
+			{ "p": ".*", "o": { "5120bb": 5000 }, "bitworkc": "7777" }
+		]"#,
+	)
+	.unwrap();
+
+	assert_eq!(match_rule(&rules, "123").unwrap().unwrap().o["5120aa"].value(), 1000);
+	assert_eq!(match_rule(&rules, "a123").unwrap().unwrap().bitworkc.as_deref(), Some("7777"));
+	assert!(match_rule(&rules[..1], "1234").unwrap().is_none());
+}
+
+fn payment_marker_script(location_id: &[u8; 36]) -> ScriptBuf {
+	Script::builder()
+		.push_opcode(OP_RETURN)
+		.push_slice(<&PushBytes>::try_from("atom".as_bytes()).unwrap())
+		.push_slice(<&PushBytes>::try_from("p".as_bytes()).unwrap())
+		.push_slice(location_id)
+		.into_script()
+}
+#[test]
+fn payment_marker_script_should_work() {
+	assert_eq!(
+		array_bytes::bytes2hex("", payment_marker_script(&[0; 36])),
+		format!("6a0461746f6d017024{}", "00".repeat(36))
+	);
+}
@@ -0,0 +1,760 @@
+// std
+use std::{collections::BTreeMap, path::Path, str::FromStr};
+// crates.io
+use bitcoin::{
+	consensus::encode, key::TapTweak, secp256k1::Secp256k1, Address, Amount, OutPoint, Psbt,
+	ScriptBuf, Sequence, Transaction, TxIn, TxOut,
+};
+// atomicalsir
+use super::{
+	operation::{CustomColor, Operation, Split},
+	resolve_ticker, sign_key_spend_psbt, Miner, MinerBuilder, Wallet,
+};
+use crate::prelude::*;
+use atomicals_electrumx::{
+	r#type::{AtomicalId, Utxo, UtxoKind},
+	Api,
+};
+
+pub async fn list(builder: MinerBuilder<'_>, wallet: &Path) -> Result<()> {
+	let w = Wallet::load(wallet, builder.network)?;
+	let m = builder.build()?;
+	let mut fts = <BTreeMap<AtomicalId, (usize, u64)>>::new();
+	// Look both addresses up in one round trip.
+	let mut utxos = m
+		.api
+		.get_unspent_addresses(vec![w.stash.address.to_string(), w.funding.address.to_string()])
+		.await?
+		.into_iter();
+	let (stash, funding) = (utxos.next().unwrap_or_default(), utxos.next().unwrap_or_default());
+
+	if w.funding.address != w.stash.address {
+		for u in funding.iter().filter(|u| !u.is_plain()) {
+			tracing::warn!(
+				"funding UTXO {}:{} carries atomicals {:?}; it is never spent for fees",
+				u.txid,
+				u.vout,
+				u.atomicals
+			);
+		}
+	}
+
+	for (u, k) in m.api.classify_utxos(stash).await? {
+		if k != UtxoKind::Ft {
+			continue;
+		}
+		if u.atomicals.len() > 1 {
+			tracing::info!(
+				"UTXO {}:{} carries multiple atomicals {:?}; transfers return the others to the stash",
+				u.txid,
+				u.vout,
+				u.atomicals
+			);
+		}
+
+		for a in u.atomicals {
+			let (c, v) = fts.entry(a.id).or_default();
+
+			*c += 1;
+			*v += a.value.unwrap_or(u.value);
+		}
+	}
+
+	for (a, (c, v)) in fts {
+		tracing::info!("{a}: {v} across {c} UTXO(s)");
+	}
+
+	Ok(())
+}
+
+pub async fn send(
+	builder: MinerBuilder<'_>,
+	wallet: &Path,
+	ticker: &str,
+	recipients: Vec<Recipient>,
+) -> Result<()> {
+	let network = builder.network;
+	let w = Wallet::load(wallet, network)?;
+	let m = builder.build()?;
+	let outputs = recipients
+		.into_iter()
+		.map(|r| {
+			let script_pubkey = match r.address {
+				Some(a) => Address::from_str(&a)?.require_network(network)?.script_pubkey(),
+				None => w.stash.address.script_pubkey(),
+			};
+
+			Ok(TxOut { value: Amount::from_sat(r.amount), script_pubkey })
+		})
+		.collect::<Result<Vec<_>>>()?;
+
+	if outputs.is_empty() {
+		Err(anyhow::anyhow!("no recipient specified"))?;
+	}
+
+	m.transfer_ft(&w, ticker, outputs).await
+}
+
+pub async fn merge(builder: MinerBuilder<'_>, wallet: &Path, ticker: &str) -> Result<()> {
+	let w = Wallet::load(wallet, builder.network)?;
+	let m = builder.build()?;
+
+	m.transfer_ft(&w, ticker, Vec::new()).await
+}
+
+impl Miner {
+	// Transfer the FT of the ticker from the stash to the outputs, in order.
+	//
+	// The selected FT UTXOs are spent first and the colored outputs come first, with the sum
+	// of their values equal to the sum of the inputs. So, under the first-in-first-out rule,
+	// every output receives exactly its value and nothing is burned. The fee is paid by a
+	// funding UTXO spent last, whose refund is never colored.
+	//
+	// If an input carries other atomicals or only colors a part of its value, the rule can't
+	// tell them apart, so the outputs are colored explicitly by a reveal instead, see
+	// `color_explicitly`.
+	//
+	// Without any output, all the FT UTXOs are merged into one.
+	async fn transfer_ft(
+		&self,
+		wallet: &Wallet,
+		ticker: &str,
+		mut outputs: Vec<TxOut>,
+	) -> Result<()> {
+		let id = resolve_ticker(
+			ticker,
+			&self.api.get_by_ticker(ticker).await?,
+			self.atomical_id.as_ref(),
+		)?;
+		let utxos = self
+			.api
+			.classify_utxos(
+				self.api
+					.get_unspent_address(wallet.stash.address.to_string())
+					.await?
+					.into_iter()
+					.filter(|u| u.carries(&id))
+					.collect(),
+			)
+			.await?
+			.into_iter()
+			.filter_map(|(u, k)| {
+				// Neither `y` nor `z` colors the NFTs.
+				if k != UtxoKind::Ft {
+					tracing::warn!(
+						"skipping UTXO {}:{} which also carries NFTs {:?}",
+						u.txid,
+						u.vout,
+						u.atomicals
+					);
+
+					return None;
+				}
+
+				Some(u)
+			})
+			.collect::<Vec<_>>();
+
+		if let Some(o) = outputs.iter().find(|o| o.value.to_sat() < Self::DUST) {
+			Err(anyhow::anyhow!(
+				"cannot transfer {} of {ticker} which is below the dust limit of {}",
+				o.value.to_sat(),
+				Self::DUST
+			))?;
+		}
+
+		let amount = outputs.iter().map(|o| o.value.to_sat()).sum::<u64>();
+		let (inputs, change) = if outputs.is_empty() {
+			if utxos.len() < 2 {
+				Err(anyhow::anyhow!(
+					"nothing to merge; found {} UTXO(s) of {ticker}",
+					utxos.len()
+				))?;
+			}
+
+			(utxos, 0)
+		} else {
+			select_utxos(utxos, &id, amount, Self::DUST).ok_or_else(|| {
+				anyhow::anyhow!("insufficient {ticker} to transfer {amount} without burning")
+			})?
+		};
+		let stash_spk = wallet.stash.address.script_pubkey();
+		let ft_amount = inputs.iter().map(|u| value_of(u, &id)).sum::<u64>();
+
+		if outputs.is_empty() {
+			outputs.push(TxOut {
+				value: Amount::from_sat(ft_amount),
+				script_pubkey: stash_spk.clone(),
+			});
+		} else if change > 0 {
+			outputs
+				.push(TxOut { value: Amount::from_sat(change), script_pubkey: stash_spk.clone() });
+		}
+
+		if let Some((op, outputs)) =
+			color_explicitly(&id, &inputs, &outputs, &stash_spk, Self::DUST)?
+		{
+			let additional_inputs = inputs
+				.iter()
+				.map(|u| {
+					Ok((
+						OutPoint::new(u.txid.parse()?, u.vout),
+						TxOut {
+							value: Amount::from_sat(u.value),
+							script_pubkey: stash_spk.clone(),
+						},
+					))
+				})
+				.collect::<Result<_>>()?;
+
+			tracing::info!(
+				"revealing `{}` to move {ft_amount} of {ticker} from {} UTXO(s)",
+				op.op_type(),
+				inputs.len()
+			);
+
+			let d = self.build_data(wallet, &op, additional_inputs, outputs, None, None).await?;
+			let r = self.execute(wallet, d).await?;
+
+			if !r.revealed {
+				Err(anyhow::anyhow!("reveal transaction {} was not broadcast", r.reveal_txid))?;
+			}
+
+			tracing::info!("`{}` revealed in {}", op.op_type(), r.reveal_txid);
+
+			return Ok(());
+		}
+
+		let ft_outputs = outputs.len();
+		let satsbyte = self.satsbyte().await? as f64;
+		let fee = (satsbyte
+			* (Self::BASE_BYTES
+				// FT inputs and the funding.
+				+ (inputs.len() + 1) as f64 * Self::INPUT_BYTES_BASE
+				// FT outputs and the refund.
+				+ (outputs.len() + 1) as f64 * Self::OUTPUT_BYTES_BASE))
+			.ceil() as u64;
+		let funding_spk = wallet.funding.address.script_pubkey();
+		let funding_utxo = self.wait_until_utxo(wallet.funding.address.to_string(), fee).await?;
+		let refund = funding_utxo.value - fee;
+
+		if refund >= Self::DUST {
+			outputs.push(TxOut {
+				value: Amount::from_sat(refund),
+				script_pubkey: funding_spk.clone(),
+			});
+		}
+
+		// Color the final transaction as the indexer would, the funding input carries nothing.
+		let (colored, burned) = color(
+			&inputs
+				.iter()
+				.map(|u| u.atomicals.first().and_then(|a| a.value).unwrap_or(u.value))
+				.collect::<Vec<_>>(),
+			&outputs.iter().map(|o| o.value.to_sat()).collect::<Vec<_>>(),
+		);
+
+		if burned != 0 {
+			Err(anyhow::anyhow!("refuse to burn {burned} of {ticker}"))?;
+		}
+		if colored
+			.iter()
+			.zip(&outputs)
+			.enumerate()
+			.any(|(i, (c, o))| *c != if i < ft_outputs { o.value.to_sat() } else { 0 })
+		{
+			Err(anyhow::anyhow!(
+				"refuse to transfer {ticker}; the outputs would be colored as {colored:?}"
+			))?;
+		}
+
+		let secp = Secp256k1::new();
+		let prevouts = inputs
+			.iter()
+			.map(|u| TxOut { value: Amount::from_sat(u.value), script_pubkey: stash_spk.clone() })
+			.chain([TxOut {
+				value: Amount::from_sat(funding_utxo.value),
+				script_pubkey: funding_spk,
+			}])
+			.collect::<Vec<_>>();
+		let mut psbt = Psbt::from_unsigned_tx(Transaction {
+			version: Self::VERSION,
+			lock_time: Self::LOCK_TIME,
+			input: inputs
+				.iter()
+				.chain([&funding_utxo])
+				.map(|u| {
+					Ok(TxIn {
+						previous_output: OutPoint::new(u.txid.parse()?, u.vout),
+						sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+						..Default::default()
+					})
+				})
+				.collect::<Result<_>>()?,
+			output: outputs,
+		})?;
+		let stash_signer = wallet.stash.pair.tap_tweak(&secp, None).to_inner();
+
+		for i in 0..inputs.len() {
+			sign_key_spend_psbt(
+				&secp,
+				&stash_signer,
+				&wallet.stash.x_only_public_key,
+				&mut psbt,
+				&prevouts,
+				i,
+			)?;
+		}
+
+		sign_key_spend_psbt(
+			&secp,
+			&wallet.funding.pair.tap_tweak(&secp, None).to_inner(),
+			&wallet.funding.x_only_public_key,
+			&mut psbt,
+			&prevouts,
+			inputs.len(),
+		)?;
+
+		let tx = psbt.extract_tx_unchecked_fee_rate();
+		let tx_hex = encode::serialize_hex(&tx);
+
+		tracing::info!(
+			"broadcasting transfer transaction {} moving {ft_amount} of {ticker} from {} UTXO(s)",
+			tx.txid(),
+			inputs.len()
+		);
+		tracing::debug!("{tx:#?}");
+		tracing::info!("{tx_hex}");
+
+		self.broadcast(&tx_hex).await?;
+
+		Ok(())
+	}
+}
+
+#[test]
+fn transfer_ft_should_work() {
+	// std
+	use std::time::Duration;
+	// crates.io
+	use bitcoin::{
+		secp256k1::{rand, Keypair},
+		Network,
+	};
+	use tokio::runtime::Runtime;
+	// atomicalsir
+	use super::{Backend, Key};
+	use crate::util::{self, FeeBound};
+	use atomicals_electrumx::{
+		mock::{Ft, Mock},
+		transport::HttpOptions,
+	};
+
+	let network = Network::Testnet;
+	let secp = Secp256k1::new();
+	let key = || {
+		let pair = Keypair::new(&secp, &mut rand::thread_rng());
+		let x_only_public_key = pair.x_only_public_key().0;
+
+		Key {
+			pair,
+			x_only_public_key,
+			address: Address::p2tr(&secp, x_only_public_key, None, network),
+		}
+	};
+	let wallet = Wallet { stash: key(), funding: key() };
+	let recipient = key().address.script_pubkey();
+
+	Runtime::new().unwrap().block_on(async {
+		let m = Mock::start().await;
+		let electrumx = [m.uri.clone()];
+		let miner = MinerBuilder {
+			thread: 2,
+			network,
+			fee_bound: &FeeBound { min: 1, max: 100 },
+			electrumx: &electrumx,
+			wait_timeout: Some(Duration::from_secs(10)),
+			bitcoind: None,
+			bitcoind_cookie: None,
+			esplora: None,
+			chain_backend: Backend::Electrumx,
+			fee_backend: Backend::Esplora,
+			http: HttpOptions::default(),
+			atomical_id: None,
+		}
+		.build()
+		.unwrap();
+		let (sir, other) = {
+			let mut s = m.state();
+
+			(s.add_ft(Ft::easy("atomicalsir")), s.add_ft(Ft::easy("other")))
+		};
+		let both = m.state().fund_atomicals(&wallet.stash.address, 1_000, &[sir, other]);
+		let alone = m.state().fund_atomicals(&wallet.stash.address, 600, &[sir]);
+
+		m.state().fund(&wallet.funding.address, 100_000);
+
+		// The UTXO carrying the FT alone is enough.
+		miner
+			.transfer_ft(
+				&wallet,
+				"atomicalsir",
+				vec![TxOut { value: Amount::from_sat(600), script_pubkey: recipient.clone() }],
+			)
+			.await
+			.unwrap();
+
+		{
+			let s = m.state();
+			let [tx] = &s.broadcasts[..] else { panic!("expected a plain transfer") };
+
+			assert_eq!(tx.input[0].previous_output.txid.to_string(), alone.txid);
+			assert_eq!(tx.output[0].value.to_sat(), 600);
+		}
+
+		let alone = m.state().fund_atomicals(&wallet.stash.address, 600, &[sir]);
+
+		m.state().fund(&wallet.funding.address, 100_000);
+
+		// The other atomical is split off by a reveal.
+		miner
+			.transfer_ft(
+				&wallet,
+				"atomicalsir",
+				vec![TxOut { value: Amount::from_sat(1_600), script_pubkey: recipient.clone() }],
+			)
+			.await
+			.unwrap();
+
+		let s = m.state();
+		let [_, commit, reveal] = &s.broadcasts[..] else {
+			panic!("expected a commit and a reveal")
+		};
+		let (_, op, payload) =
+			util::parse_reveal_script(reveal.input[0].witness.tapscript().unwrap()).unwrap();
+
+		assert_eq!(reveal.input[0].previous_output, OutPoint::new(commit.txid(), 0));
+		assert_eq!(
+			reveal.input[1..]
+				.iter()
+				.map(|i| i.previous_output.txid.to_string())
+				.collect::<Vec<_>>(),
+			[alone.txid, both.txid]
+		);
+		assert_eq!(
+			reveal.output,
+			[
+				TxOut { value: Amount::from_sat(1_600), script_pubkey: recipient },
+				TxOut {
+					value: Amount::from_sat(1_000),
+					script_pubkey: wallet.stash.address.script_pubkey()
+				}
+			]
+		);
+		assert_eq!(op, "y");
+		assert_eq!(
+			payload,
+			util::cbor(&BTreeMap::from([(sir.to_string(), 0), (other.to_string(), 1_600)]))
+				.unwrap()
+		);
+	});
+}
+
+#[derive(Clone, Debug)]
+pub struct Recipient {
+	// Send to the stash if absent.
+	pub address: Option<String>,
+	pub amount: u64,
+}
+impl Recipient {
+	pub fn from_str(s: &str) -> Result<Self> {
+		let (address, amount) = match s.rsplit_once(':') {
+			Some((a, v)) => (Some(a.into()), v),
+			None => (None, s),
+		};
+
+		Ok(Self { address, amount: amount.parse()? })
+	}
+}
+#[test]
+fn recipient_from_str_should_work() {
+	let r = Recipient::from_str("tb1qaddress:1000").unwrap();
+
+	assert_eq!(r.address.as_deref(), Some("tb1qaddress"));
+	assert_eq!(r.amount, 1000);
+	assert!(Recipient::from_str("600").unwrap().address.is_none());
+	assert!(Recipient::from_str("tb1qaddress:").is_err());
+}
+
+// Select the smallest UTXOs first to consolidate the dust ones along the way.
+//
+// The UTXOs carrying the FT alone go first, so the others are only spent when they fall short.
+// The change, if any, must be spendable on its own.
+fn select_utxos(
+	mut utxos: Vec<Utxo>,
+	id: &AtomicalId,
+	amount: u64,
+	dust: u64,
+) -> Option<(Vec<Utxo>, u64)> {
+	utxos.sort_by_key(|u| (!is_clean(u), value_of(u, id)));
+
+	let mut sum = 0;
+	let (i, change) = utxos.iter().enumerate().find_map(|(i, u)| {
+		sum += value_of(u, id);
+
+		let change = sum.checked_sub(amount)?;
+
+		(change == 0 || change >= dust).then_some((i, change))
+	})?;
+
+	utxos.truncate(i + 1);
+
+	Some((utxos, change))
+}
+#[test]
+fn select_utxos_should_work() {
+	// crates.io
+	use bitcoin::{hashes::Hash, Txid};
+	// atomicalsir
+	use atomicals_electrumx::r#type::UtxoAtomical;
+
+	let id = |i| AtomicalId { txid: Txid::all_zeros(), index: i };
+	let utxos = [1_000, 600, 2_000]
+		.into_iter()
+		.map(|value| Utxo {
+			txid: String::new(),
+			vout: 0,
+			value,
+			atomicals: vec![UtxoAtomical { id: id(0), value: None }],
+		})
+		.collect::<Vec<_>>();
+	let values = |utxos: &[Utxo], amount| {
+		select_utxos(utxos.to_vec(), &id(0), amount, 546)
+			.map(|(us, c)| (us.into_iter().map(|u| u.value).collect::<Vec<_>>(), c))
+	};
+
+	assert_eq!(values(&utxos, 600), Some((vec![600], 0)));
+	assert_eq!(values(&utxos, 1_000), Some((vec![600, 1_000], 600)));
+	// The change of `100` would be dust.
+	assert_eq!(values(&utxos, 1_500), Some((vec![600, 1_000, 2_000], 2_100)));
+	assert_eq!(values(&utxos, 3_500), None);
+	assert_eq!(values(&utxos, 3_600), Some((vec![600, 1_000, 2_000], 0)));
+
+	let mut utxos = utxos;
+
+	// Carrying another atomical, or only `500` of the FT.
+	utxos[1].atomicals.push(UtxoAtomical { id: id(1), value: None });
+	utxos.push(Utxo {
+		txid: String::new(),
+		vout: 0,
+		value: 1_000,
+		atomicals: vec![UtxoAtomical { id: id(0), value: Some(500) }],
+	});
+
+	// The others are spent last.
+	assert_eq!(values(&utxos, 600), Some((vec![1_000, 2_000], 2_400)));
+	assert_eq!(values(&utxos, 3_500), Some((vec![1_000, 2_000, 1_000], 0)));
+	assert_eq!(values(&utxos, 4_100), Some((vec![1_000, 2_000, 1_000, 600], 0)));
+}
+
+// The value of the atomical carried by the UTXO, which colors all of it unless told otherwise.
+fn value_of(u: &Utxo, id: &AtomicalId) -> u64 {
+	u.atomicals.iter().find(|a| &a.id == id).and_then(|a| a.value).unwrap_or(u.value)
+}
+
+// Whether the first-in-first-out rule colors the UTXO as a whole with a single atomical.
+fn is_clean(u: &Utxo) -> bool {
+	u.atomicals.len() <= 1 && u.atomicals.iter().all(|a| a.value.is_none_or(|v| v == u.value))
+}
+
+// Follow the first-in-first-out coloring rule of a single FT.
+//
+// The outputs are colored in order while the remaining input value covers them entirely. The
+// first output which isn't covered ends the coloring and anything left is burned.
+//
+// Return the colored value of each output and the burned value.
+fn color(inputs: &[u64], outputs: &[u64]) -> (Vec<u64>, u64) {
+	let mut remaining = inputs.iter().sum::<u64>();
+	let mut colored = vec![0; outputs.len()];
+
+	for (c, o) in colored.iter_mut().zip(outputs) {
+		if remaining == 0 || *o > remaining {
+			break;
+		}
+
+		*c = *o;
+		remaining -= o;
+	}
+
+	(colored, remaining)
+}
+#[test]
+fn color_should_work() {
+	assert_eq!(color(&[1_000, 600], &[1_600]), (vec![1_600], 0));
+	assert_eq!(color(&[1_000, 600], &[600, 1_000]), (vec![600, 1_000], 0));
+	assert_eq!(color(&[1_000, 600], &[1_000]), (vec![1_000], 600));
+	// The second output is not covered entirely.
+	assert_eq!(color(&[1_000, 600], &[1_000, 1_000]), (vec![1_000, 0], 600));
+	assert_eq!(color(&[1_000], &[]), (vec![], 1_000));
+	// The refund after the FT outputs stays uncolored.
+	assert_eq!(color(&[1_000, 600], &[1_600, 5_000]), (vec![1_600, 0], 0));
+	// A partially colored input leaves the last output short.
+	assert_eq!(color(&[1_000, 500], &[1_000, 600, 5_000]), (vec![1_000, 0, 0], 500));
+}
+
+// Color the outputs of a transfer explicitly, unless every input carries the FT alone.
+//
+// The outputs of the FT come first, then the other atomicals of the inputs are returned to the
+// stash. With every input colored as a whole, a `y` operation gives each of them an output of
+// its value, skipping the outputs before. Otherwise the indexer colors partially, so a `z`
+// operation packs them into one output and the uncolored rest of the inputs is refunded to the
+// stash.
+//
+// Return the operation and all the outputs, each atomical is colored to exactly its value.
+fn color_explicitly(
+	id: &AtomicalId,
+	inputs: &[Utxo],
+	outputs: &[TxOut],
+	stash: &ScriptBuf,
+	dust: u64,
+) -> Result<Option<(Operation, Vec<TxOut>)>> {
+	if inputs.iter().all(is_clean) {
+		return Ok(None);
+	}
+
+	let mut outputs = outputs.to_vec();
+	let mut others = <BTreeMap<AtomicalId, u64>>::new();
+
+	for u in inputs {
+		for a in u.atomicals.iter().filter(|a| &a.id != id) {
+			*others.entry(a.id).or_default() += a.value.unwrap_or(u.value);
+		}
+	}
+
+	let output = |value| TxOut { value: Amount::from_sat(value), script_pubkey: stash.clone() };
+
+	if inputs.iter().any(|u| u.atomicals.iter().any(|a| a.value.is_some_and(|v| v != u.value))) {
+		let mut colors = BTreeMap::from([(
+			id.to_string(),
+			(0..).zip(outputs.iter().map(|o| o.value.to_sat())).collect::<BTreeMap<_, _>>(),
+		)]);
+
+		if let Some(max) = others.values().max() {
+			let i = outputs.len() as u32;
+
+			// An atomical colors at most the value of the output.
+			outputs.push(output((*max).max(dust)));
+
+			for (a, v) in others {
+				colors.insert(a.to_string(), [(i, v)].into());
+			}
+		}
+
+		let surplus = inputs
+			.iter()
+			.map(|u| u.value)
+			.sum::<u64>()
+			.saturating_sub(outputs.iter().map(|o| o.value.to_sat()).sum());
+
+		if surplus >= dust {
+			outputs.push(output(surplus));
+		}
+
+		Ok(Some((Operation::Z(CustomColor(colors)), outputs)))
+	} else {
+		let mut skip = outputs.iter().map(|o| o.value.to_sat()).sum::<u64>();
+		let mut skips = BTreeMap::from([(id.to_string(), 0)]);
+
+		for (a, v) in others {
+			if v < dust {
+				Err(anyhow::anyhow!(
+					"cannot return {v} of {a} which is below the dust limit of {dust}"
+				))?;
+			}
+
+			skips.insert(a.to_string(), skip);
+			outputs.push(output(v));
+
+			skip += v;
+		}
+
+		Ok(Some((Operation::Y(Split(skips)), outputs)))
+	}
+}
+#[test]
+fn color_explicitly_should_work() {
+	// crates.io
+	use bitcoin::{hashes::Hash, Txid};
+	// atomicalsir
+	use crate::util;
+	use atomicals_electrumx::r#type::UtxoAtomical;
+
+	let id = |i| AtomicalId { txid: Txid::all_zeros(), index: i };
+	let utxo = |value, atomicals: &[(u32, Option<u64>)]| Utxo {
+		txid: String::new(),
+		vout: 0,
+		value,
+		atomicals: atomicals.iter().map(|&(i, value)| UtxoAtomical { id: id(i), value }).collect(),
+	};
+	let stash = ScriptBuf::new();
+	let outputs = |values: &[u64]| {
+		values
+			.iter()
+			.map(|&v| TxOut { value: Amount::from_sat(v), script_pubkey: stash.clone() })
+			.collect::<Vec<_>>()
+	};
+	let color = |inputs: &[Utxo], values: &[u64]| {
+		color_explicitly(&id(0), inputs, &outputs(values), &stash, 546).unwrap().map(|(op, os)| {
+			(
+				op.op_type(),
+				array_bytes::bytes2hex("", op.payload().unwrap()),
+				os.into_iter().map(|o| o.value.to_sat()).collect::<Vec<_>>(),
+			)
+		})
+	};
+
+	// The first-in-first-out rule is enough.
+	assert_eq!(color(&[utxo(1_000, &[(0, None)]), utxo(600, &[(0, Some(600))])], &[1_600]), None);
+
+	// The other atomical is returned right after the FT.
+	let (op, payload, values) =
+		color(&[utxo(1_000, &[(0, None), (1, None)]), utxo(600, &[(0, None)])], &[1_000, 600])
+			.unwrap();
+
+	assert_eq!((op, values), ("y", vec![1_000, 600, 1_000]));
+	assert_eq!(
+		util::cbor(&BTreeMap::from([(id(0).to_string(), 0), (id(1).to_string(), 1_600)])).unwrap(),
+		array_bytes::hex2bytes_unchecked(&payload)
+	);
+
+	// Partially colored, the other atomicals share an output and the rest is refunded.
+	let (op, payload, values) = color(
+		&[
+			utxo(3_000, &[(0, Some(700)), (1, Some(300))]),
+			utxo(1_000, &[(2, Some(1_000)), (0, None)]),
+		],
+		&[700, 1_000],
+	)
+	.unwrap();
+
+	assert_eq!((op, values), ("z", vec![700, 1_000, 1_000, 1_300]));
+	assert_eq!(
+		util::cbor(&CustomColor(BTreeMap::from([
+			(id(0).to_string(), [(0, 700), (1, 1_000)].into()),
+			(id(1).to_string(), [(2, 300)].into()),
+			(id(2).to_string(), [(2, 1_000)].into()),
+		])))
+		.unwrap(),
+		array_bytes::hex2bytes_unchecked(&payload)
+	);
+
+	// The other atomical couldn't be returned on its own.
+	assert!(color_explicitly(
+		&id(0),
+		&[utxo(1_000, &[(0, None)]), utxo(400, &[(0, None), (1, None)])],
+		&outputs(&[1_400]),
+		&stash,
+		546,
+	)
+	.is_err());
+}
@@ -14,15 +14,12 @@ mod prelude {
 }
 use prelude::*;
 
-// crates.io
-use clap::Parser;
-
 #[tokio::main]
 async fn main() -> Result<()> {
 	color_eyre::install().unwrap();
 	tracing_subscriber::fmt::init();
 
-	Cli::parse().run().await?;
+	Cli::parse_or_mine().run().await?;
 
 	Ok(())
 }
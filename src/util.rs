@@ -6,18 +6,21 @@ use std::{
 };
 // crates.io
 use bitcoin::{
+	hex::DisplayHex,
 	opcodes::{
 		all::{OP_CHECKSIG, OP_ENDIF, OP_IF, OP_RETURN},
 		OP_0,
 	},
-	script::PushBytes,
+	script::{Instruction, PushBytes},
 	secp256k1::Keypair,
 	PrivateKey, Script, ScriptBuf, XOnlyPublicKey,
 };
 use rand::Rng;
+use reqwest::ClientBuilder;
 use serde::{Deserialize, Serialize};
 // atomicalsir
 use crate::prelude::*;
+use atomicals_electrumx::transport::HttpOptions;
 
 #[derive(Clone, Debug)]
 pub struct FeeBound {
@@ -39,14 +42,19 @@ impl FeeBound {
 	}
 }
 
-pub async fn query_fee() -> Result<u64> {
+// Fastest fee rate recommended by mempool.space, through the shared proxy and headers.
+pub async fn query_fee(http: &HttpOptions) -> Result<u64> {
 	#[derive(Debug, Deserialize)]
 	#[serde(rename_all = "camelCase")]
 	struct FastestFee {
 		fastest_fee: u64,
 	}
 
-	Ok(reqwest::get("https://mempool.space/api/v1/fees/recommended")
+	Ok(http
+		.apply(ClientBuilder::new())?
+		.build()?
+		.get("https://mempool.space/api/v1/fees/recommended")
+		.send()
 		.await?
 		.json::<FastestFee>()
 		.await?
@@ -79,12 +87,13 @@ where
 #[test]
 fn cbor_should_work() {
 	// atomicalsir
-	use crate::engine::rust::{Payload, PayloadWrapper};
+	use crate::engine::rust::operation::{Dmt, DmtArgs};
 
 	assert_eq!(
-		cbor(&PayloadWrapper {
-			args: Payload {
-				bitworkc: "aabbcc".into(),
+		cbor(&Dmt {
+			args: DmtArgs {
+				bitworkc: Some("aabbcc".into()),
+				bitworkr: None,
 				mint_ticker: "quark".into(),
 				nonce: 9999999,
 				time: 1704057427
@@ -92,6 +101,8 @@ fn cbor_should_work() {
 		}).unwrap(),
 		array_bytes::hex2bytes_unchecked("a16461726773a468626974776f726b63666161626263636b6d696e745f7469636b657265717561726b656e6f6e63651a0098967f6474696d651a6591da53")
 	);
+	// Byte strings must be encoded as the major type 2 rather than an array of integers.
+	assert_eq!(cbor(&serde_bytes::Bytes::new(b"atom")).unwrap(), [0x44, 0x61, 0x74, 0x6f, 0x6d]);
 }
 
 pub fn keypair_from_wif<S>(wif: S) -> Result<Keypair>
@@ -130,7 +141,7 @@ pub fn build_reval_script(
 #[test]
 fn build_reval_script_should_work() {
 	// atomicalsir
-	use crate::engine::rust::{Payload, PayloadWrapper};
+	use crate::engine::rust::operation::{Dmt, DmtArgs};
 
 	// assert_eq!(
 	// 	build_reval_script(
@@ -153,9 +164,10 @@ fn build_reval_script_should_work() {
 			build_reval_script(
 				&keypair_from_wif("L4VgnxVoaPRaptd4yW19wwd7v9dzJvQn478AKwucbaQifPFBacrp").unwrap().x_only_public_key().0,
 				"dmt",
-				&cbor(&PayloadWrapper {
-					args: Payload {
-						bitworkc: "aabbcc".into(),
+				&cbor(&Dmt {
+					args: DmtArgs {
+						bitworkc: Some("aabbcc".into()),
+						bitworkr: None,
 						mint_ticker: "quark".into(),
 						nonce: 9999999,
 						time: 1704057427
@@ -166,6 +178,148 @@ fn build_reval_script_should_work() {
 		),
 		"207e41d0ce6e41328e17ec13076603fc9d7a1d41fb1b497af09cdfbf9b648f7480ac00630461746f6d03646d743ea16461726773a468626974776f726b63666161626263636b6d696e745f7469636b657265717561726b656e6f6e63651a0098967f6474696d651a6591da5368"
 	);
+
+	// Payloads larger than 520 bytes are split into multiple `OP_PUSHDATA2` pushes.
+	let s = build_reval_script(
+		&keypair_from_wif("L4VgnxVoaPRaptd4yW19wwd7v9dzJvQn478AKwucbaQifPFBacrp")
+			.unwrap()
+			.x_only_public_key()
+			.0,
+		"nft",
+		&[0; 1_100],
+	);
+	let pushes = s
+		.instructions()
+		.filter_map(|i| i.unwrap().push_bytes().map(|b| b.len()))
+		.collect::<Vec<_>>();
+
+	assert_eq!(pushes, [32, 0, 4, 3, 520, 520, 60]);
+}
+
+// The inverse of `build_reval_script`.
+//
+// Return the signer, the operation type and the joined payload.
+pub fn parse_reveal_script(script: &Script) -> Result<(XOnlyPublicKey, String, Vec<u8>)> {
+	use Instruction::{Op, PushBytes as Push};
+
+	let is = script
+		.instructions()
+		.collect::<Result<Vec<_>, _>>()
+		.map_err(|e| anyhow::anyhow!("invalid script due to {e}"))?;
+	let [Push(k), Op(cs), Push(z), Op(if_), Push(atom), Push(op), chunks @ .., Op(end)] =
+		is.as_slice()
+	else {
+		Err(anyhow::anyhow!("not a reveal script"))?
+	};
+
+	// `OP_0` is parsed as an empty push.
+	if *cs != OP_CHECKSIG || !z.is_empty() || *if_ != OP_IF || *end != OP_ENDIF {
+		Err(anyhow::anyhow!("not a reveal script"))?;
+	}
+	if atom.as_bytes() != b"atom" {
+		Err(anyhow::anyhow!("missing `atom` envelope"))?;
+	}
+
+	let payload = chunks.iter().try_fold(Vec::new(), |mut p, i| {
+		let Push(c) = i else { Err(anyhow::anyhow!("unexpected {i:?} in payload"))? };
+
+		p.extend_from_slice(c.as_bytes());
+
+		Ok::<_, Error>(p)
+	})?;
+
+	Ok((
+		XOnlyPublicKey::from_slice(k.as_bytes())?,
+		String::from_utf8(op.as_bytes().to_vec())?,
+		payload,
+	))
+}
+#[test]
+fn parse_reveal_script_should_work() {
+	let xpk = keypair_from_wif("L4VgnxVoaPRaptd4yW19wwd7v9dzJvQn478AKwucbaQifPFBacrp")
+		.unwrap()
+		.x_only_public_key()
+		.0;
+	let payload = (0..1_100).map(|i| i as u8).collect::<Vec<_>>();
+
+	assert_eq!(
+		parse_reveal_script(&build_reval_script(&xpk, "nft", &payload)).unwrap(),
+		(xpk, "nft".into(), payload)
+	);
+	assert_eq!(
+		parse_reveal_script(&build_reval_script(&xpk, "dat", &[])).unwrap().2,
+		Vec::<u8>::new()
+	);
+
+	let mut s = build_reval_script(&xpk, "dmt", &[0]).into_bytes();
+
+	// `atom` to `atoM`.
+	s[38] = b'M';
+
+	assert!(parse_reveal_script(Script::from_bytes(&s)).is_err());
+	// Without `OP_ENDIF`.
+	assert!(parse_reveal_script(Script::from_bytes(&s[..s.len() - 1])).is_err());
+}
+
+// Decode a CBOR payload into JSON, with byte strings as hex.
+pub fn cbor_to_json(cbor: &[u8]) -> Result<serde_json::Value> {
+	fn convert(v: ciborium::Value) -> Result<serde_json::Value> {
+		use ciborium::Value as C;
+		use serde_json::Value as J;
+
+		Ok(match v {
+			C::Integer(i) => {
+				let i = i128::from(i);
+
+				if let Ok(i) = u64::try_from(i) {
+					J::from(i)
+				} else {
+					J::from(i64::try_from(i)?)
+				}
+			},
+			C::Bytes(b) => J::String(b.to_lower_hex_string()),
+			C::Float(f) => J::from(f),
+			C::Text(t) => J::String(t),
+			C::Bool(b) => J::Bool(b),
+			C::Null => J::Null,
+			C::Tag(_, v) => convert(*v)?,
+			C::Array(a) => J::Array(a.into_iter().map(convert).collect::<Result<_>>()?),
+			C::Map(m) => J::Object(
+				m.into_iter()
+					.map(|(k, v)| {
+						let k = match convert(k)? {
+							J::String(k) => k,
+							k => k.to_string(),
+						};
+
+						Ok((k, convert(v)?))
+					})
+					.collect::<Result<_>>()?,
+			),
+			v => Err(anyhow::anyhow!("unsupported CBOR value {v:?}"))?,
+		})
+	}
+
+	convert(ciborium::from_reader(cbor).map_err(|e| anyhow::anyhow!("invalid CBOR due to {e}"))?)
+}
+#[test]
+fn cbor_to_json_should_work() {
+	assert_eq!(
+		cbor_to_json(
+			&cbor(&(
+				"atom",
+				serde_bytes::Bytes::new(&[0xff]),
+				-1,
+				u64::MAX,
+				true,
+				[(1, 2)].into_iter().collect::<std::collections::BTreeMap<_, _>>()
+			))
+			.unwrap()
+		)
+		.unwrap(),
+		serde_json::json!(["atom", "ff", -1, u64::MAX, true, { "1": 2 }])
+	);
+	assert!(cbor_to_json(&[0xa1]).is_err());
 }
 
 pub fn cache<S1, S2>(txid: S1, tx: S2) -> Result<()>
@@ -47,10 +47,8 @@ impl Wallet {
 
 						if p.extension().map(|e| e == "json") == Some(true) {
 							Wallet::load(&p)
-								.map(|w| {
+								.inspect(|_| {
 									tracing::info!("loaded wallet: {}", p.display());
-
-									w
 								})
 								.map_err(|e| {
 									tracing::error!(